@@ -0,0 +1,79 @@
+//! Reads single-line inputs of growing size back.
+//!
+//! The throughput should stay (roughly) the same for every size, otherwise collecting the
+//! read-back bytes isn't linear anymore.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use read_collection::{BufReadBack, BufReadBacker, ReadBack};
+
+const MIB: usize = 1024 * 1024;
+const SIZES: [usize; 3] = [MIB, 10 * MIB, 100 * MIB];
+
+fn single_line(size: usize) -> Vec<u8> {
+    let mut line = vec![b'a'; size];
+    line[0] = b'\n';
+    line
+}
+
+fn read_back_until(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_back_until");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let data = single_line(size);
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| {
+                let mut reader = BufReadBacker::new(data.as_slice());
+                let mut buffer = Vec::new();
+                reader.read_back_until(b'\n', &mut buffer).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn read_back_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_back_line");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let data = single_line(size);
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| {
+                let mut reader = BufReadBacker::new(data.as_slice());
+                let mut line = String::new();
+                reader.read_back_line(&mut line).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn read_back_to_end(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_back_to_end");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let data = single_line(size);
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| {
+                // `BufReadBacker` uses the default implementation, unlike `&[u8]`
+                let mut reader = BufReadBacker::new(data.as_slice());
+                let mut buffer = Vec::new();
+                reader.read_back_to_end(&mut buffer).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, read_back_until, read_back_line, read_back_to_end);
+criterion_main!(benches);
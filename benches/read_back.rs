@@ -0,0 +1,224 @@
+//! Compares the read-back paths with their forward equivalents of `std::io`.
+//!
+//! Every benchmark consumes the whole input, so `forward` and `read_back` of a group process
+//! the same amount of bytes.
+use std::{
+    fs::File,
+    hint::black_box,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use read_collection::{BufReadBack, BufReadBacker, ReadBack};
+
+const DATA_SIZE: usize = 16 * 1024 * 1024;
+const CHUNK_SIZE: usize = 8 * 1024;
+const SMALL_READ_SIZE: usize = 64;
+const CAPACITIES: [usize; 4] = [512, 8 * 1024, 64 * 1024, 1024 * 1024];
+
+/// Log-like lines of different lengths.
+fn data() -> Vec<u8> {
+    let mut data = Vec::with_capacity(DATA_SIZE + 128);
+    let mut line_nr: usize = 0;
+    while data.len() < DATA_SIZE {
+        let padding = "x".repeat(line_nr % 97);
+        writeln!(data, "[{:>10}] INFO some message {}", line_nr, padding).unwrap();
+        line_nr += 1;
+    }
+    data
+}
+
+/// Writes the data into a file next to the other build artifacts, so it doesn't end up in `/tmp` (which may be a tmpfs).
+fn data_file(data: &[u8]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("read_back_bench.txt");
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+fn open_at_end(path: &PathBuf) -> File {
+    let mut file = File::open(path).unwrap();
+    file.seek(SeekFrom::End(0)).unwrap();
+    file
+}
+
+fn u8_slice(c: &mut Criterion) {
+    let data = data();
+    let mut group = c.benchmark_group("u8_slice");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("forward", |b| {
+        b.iter(|| {
+            let mut reader = data.as_slice();
+            let mut buffer = [0; CHUNK_SIZE];
+            while reader.read(&mut buffer).unwrap() != 0 {
+                black_box(&buffer);
+            }
+        })
+    });
+
+    group.bench_function("read_back", |b| {
+        b.iter(|| {
+            let mut reader = data.as_slice();
+            let mut buffer = [0; CHUNK_SIZE];
+            while reader.read_back(&mut buffer).unwrap() != 0 {
+                black_box(&buffer);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn file(c: &mut Criterion) {
+    let data = data();
+    let path = data_file(&data);
+    let mut group = c.benchmark_group("file");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+
+    group.bench_function("forward", |b| {
+        b.iter(|| {
+            let mut file = File::open(&path).unwrap();
+            let mut buffer = [0; CHUNK_SIZE];
+            while file.read(&mut buffer).unwrap() != 0 {
+                black_box(&buffer);
+            }
+        })
+    });
+
+    group.bench_function("read_back", |b| {
+        b.iter(|| {
+            let mut file = open_at_end(&path);
+            let mut buffer = [0; CHUNK_SIZE];
+            while file.read_back(&mut buffer).unwrap() != 0 {
+                black_box(&buffer);
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// Many small reads, so the capacity of the internal buffer matters.
+fn buffered_file(c: &mut Criterion) {
+    let data = data();
+    let path = data_file(&data);
+    let mut group = c.benchmark_group("buffered_file");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(20);
+
+    for capacity in CAPACITIES {
+        group.bench_with_input(
+            BenchmarkId::new("forward", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| {
+                    let mut reader = BufReader::with_capacity(capacity, File::open(&path).unwrap());
+                    let mut buffer = [0; SMALL_READ_SIZE];
+                    while reader.read(&mut buffer).unwrap() != 0 {
+                        black_box(&buffer);
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("read_back", capacity),
+            &capacity,
+            |b, &capacity| {
+                b.iter(|| {
+                    let mut reader = BufReadBacker::with_capacity(capacity, open_at_end(&path));
+                    let mut buffer = [0; SMALL_READ_SIZE];
+                    while reader.read_back(&mut buffer).unwrap() != 0 {
+                        black_box(&buffer);
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn lines(c: &mut Criterion) {
+    let data = data();
+    let path = data_file(&data);
+    let mut group = c.benchmark_group("lines");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("forward", |b| {
+        b.iter(|| BufReader::new(File::open(&path).unwrap()).lines().count())
+    });
+
+    group.bench_function("read_back", |b| {
+        b.iter(|| {
+            BufReadBacker::new(open_at_end(&path))
+                .read_back_lines()
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+fn until(c: &mut Criterion) {
+    let data = data();
+    let path = data_file(&data);
+    let mut group = c.benchmark_group("until");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("forward", |b| {
+        b.iter(|| {
+            let mut reader = BufReader::new(File::open(&path).unwrap());
+            let mut buffer = Vec::new();
+            while reader.read_until(b'\n', &mut buffer).unwrap() != 0 {
+                buffer.clear();
+            }
+        })
+    });
+
+    group.bench_function("read_back", |b| {
+        b.iter(|| {
+            let mut reader = BufReadBacker::new(open_at_end(&path));
+            let mut buffer = Vec::new();
+            while reader.read_back_until(b'\n', &mut buffer).unwrap() != 0 {
+                buffer.clear();
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn to_end(c: &mut Criterion) {
+    let data = data();
+    let path = data_file(&data);
+    let mut group = c.benchmark_group("to_end");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(20);
+
+    group.bench_function("forward", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            BufReader::new(File::open(&path).unwrap())
+                .read_to_end(&mut buffer)
+                .unwrap()
+        })
+    });
+
+    group.bench_function("read_back", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::new();
+            BufReadBacker::new(open_at_end(&path))
+                .read_back_to_end(&mut buffer)
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, u8_slice, file, buffered_file, lines, until, to_end);
+criterion_main!(benches);
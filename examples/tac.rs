@@ -0,0 +1,266 @@
+//! A `tac` clone: Prints files with their records (lines by default) in reverse order.
+//!
+//! Usage: `cargo run --example tac -- [-b] [-r] [-s SEPARATOR] [FILE]...`
+//!
+//! - `-b`, `--before`: attach the separator before instead of after each record
+//! - `-r`, `--regex`: interpret the separator as a regular expression (in the syntax of the `regex` crate, so
+//!   alternations are written as `a|b` instead of `a\|b` as in GNU `tac`)
+//! - `-s`, `--separator SEPARATOR`: use `SEPARATOR` instead of a newline
+//!
+//! Without a file, or if the file is `-`, the standard input is read.
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    process::ExitCode,
+};
+
+use read_collection::{BufReadBack, BufReadBacker};
+use regex::bytes::Regex;
+
+/// The amount of bytes which are read back at once while searching for a regex separator.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// What separates the records.
+#[derive(Debug)]
+pub enum Separator {
+    /// A single byte, like the default newline.
+    Byte(u8),
+    /// A regular expression which is anchored at its start.
+    Regex(Regex),
+}
+
+impl Separator {
+    pub fn new(separator: &str, regex: bool) -> Result<Self, String> {
+        if separator.is_empty() {
+            return Err("separator cannot be empty".to_string());
+        }
+
+        match separator.as_bytes() {
+            [byte] if !regex => Ok(Self::Byte(*byte)),
+            _ => {
+                let pattern = if regex {
+                    separator.to_string()
+                } else {
+                    regex::escape(separator)
+                };
+
+                Regex::new(&format!(r"\A(?:{})", pattern))
+                    .map(Self::Regex)
+                    .map_err(|err| err.to_string())
+            }
+        }
+    }
+}
+
+/// Writes the records of `reader` in reverse order into `out`.
+///
+/// Like GNU `tac`, the separator belongs to the end of the record it terminates, or to the start of the record it
+/// precedes if `before` is set.
+pub fn tac<R: BufReadBack, W: Write>(
+    mut reader: R,
+    separator: &Separator,
+    before: bool,
+    out: &mut W,
+) -> io::Result<()> {
+    match separator {
+        Separator::Byte(byte) => tac_byte(&mut reader, *byte, before, out),
+        Separator::Regex(regex) => tac_regex(&mut reader, regex, before, out),
+    }
+}
+
+/// `read_back_until` hands us each record with its leading separator, if there's one.
+///
+/// `read_back_split` isn't enough here: it drops the separators, so an empty record at the start
+/// of the file (if the file starts with a separator) would get lost.
+fn tac_byte<R: BufReadBack, W: Write>(
+    reader: &mut R,
+    separator: u8,
+    before: bool,
+    out: &mut W,
+) -> io::Result<()> {
+    let mut record = Vec::new();
+    // the separator in front of the previous record terminates the current one
+    let mut pending_separator = false;
+
+    loop {
+        record.clear();
+        if reader.read_back_until(separator, &mut record)? == 0 {
+            break;
+        }
+
+        if before {
+            out.write_all(&record)?;
+        } else {
+            let (has_separator, content) = match record.split_first() {
+                Some((first, content)) if *first == separator => (true, content),
+                _ => (false, record.as_slice()),
+            };
+
+            out.write_all(content)?;
+            if pending_separator {
+                out.write_all(&[separator])?;
+            }
+            pending_separator = has_separator;
+        }
+    }
+
+    // the source starts with a separator, so there's an empty record in front of it
+    if pending_separator {
+        out.write_all(&[separator])?;
+    }
+
+    Ok(())
+}
+
+/// Searches backwards for the separator with the latest start, like GNU `tac` does.
+///
+/// Whether the separator matches at a position only depends on the bytes behind it, so the
+/// bytes in front of the current window are only read back if there's no match in it.
+fn tac_regex<R: BufReadBack, W: Write>(
+    reader: &mut R,
+    regex: &Regex,
+    before: bool,
+    out: &mut W,
+) -> io::Result<()> {
+    // the bytes which haven't been written yet
+    let mut window: Vec<u8> = Vec::new();
+    // the separator has to end before `limit`, so it doesn't overlap with the previous one
+    let mut limit = 0;
+    // the next position where the separator could start, `None` if we have to read back more
+    let mut pos: Option<usize> = None;
+
+    loop {
+        let Some(start) = pos else {
+            let chunk_size = std::cmp::max(CHUNK_SIZE, window.len());
+            let mut chunk = vec![0; chunk_size];
+            let amount = reader.read_back(&mut chunk)?;
+
+            if amount == 0 {
+                // we've reached the start, so the rest is the first record
+                return out.write_all(&window);
+            }
+
+            window.splice(0..0, chunk[..amount].iter().copied());
+            limit += amount;
+            pos = Some(amount - 1);
+            continue;
+        };
+
+        match regex.find(&window[start..limit]) {
+            Some(m) if !m.is_empty() => {
+                let (sep_start, sep_end) = (start, start + m.end());
+                let record_start = if before { sep_start } else { sep_end };
+
+                out.write_all(&window[record_start..])?;
+                window.truncate(record_start);
+                limit = sep_start;
+            }
+            _ => (),
+        }
+
+        pos = start.checked_sub(1);
+    }
+}
+
+/// The standard input isn't seekable, so it's spooled into a temporary file if it's big.
+#[cfg(feature = "spool")]
+fn tac_stdin<W: Write>(separator: &Separator, before: bool, out: &mut W) -> io::Result<()> {
+    read_collection::SpooledReadBack::new(io::stdin().lock())
+        .and_then(|reader| tac(reader, separator, before, out))
+}
+
+/// The standard input isn't seekable, so it's read into memory first.
+#[cfg(not(feature = "spool"))]
+fn tac_stdin<W: Write>(separator: &Separator, before: bool, out: &mut W) -> io::Result<()> {
+    let mut content = Vec::new();
+    io::Read::read_to_end(&mut io::stdin().lock(), &mut content)?;
+    tac(content.as_slice(), separator, before, out)
+}
+
+/// Runs `tac` with the given command line arguments (without the program name).
+pub fn run<W: Write>(args: &[String], out: &mut W) -> Result<(), String> {
+    let mut before = false;
+    let mut regex = false;
+    let mut separator = "\n".to_string();
+    let mut files = Vec::new();
+
+    let mut args = args.iter();
+    let mut only_files = false;
+    while let Some(arg) = args.next() {
+        if only_files || arg == "-" || !arg.starts_with('-') {
+            files.push(arg.clone());
+            continue;
+        }
+
+        match arg.as_str() {
+            "--" => only_files = true,
+            "--before" => before = true,
+            "--regex" => regex = true,
+            "--separator" => {
+                separator = args
+                    .next()
+                    .cloned()
+                    .ok_or("option '--separator' requires an argument")?
+            }
+            long if long.starts_with("--separator=") => {
+                separator = long["--separator=".len()..].to_string()
+            }
+            long if long.starts_with("--") => {
+                return Err(format!("unrecognized option '{}'", long))
+            }
+            short => {
+                for (index, flag) in short.char_indices().skip(1) {
+                    match flag {
+                        'b' => before = true,
+                        'r' => regex = true,
+                        's' => {
+                            let rest = &short[index + 1..];
+                            separator = if rest.is_empty() {
+                                args.next()
+                                    .cloned()
+                                    .ok_or("option requires an argument -- 's'")?
+                            } else {
+                                rest.to_string()
+                            };
+                            break;
+                        }
+                        other => return Err(format!("invalid option -- '{}'", other)),
+                    }
+                }
+            }
+        }
+    }
+
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    let separator = Separator::new(&separator, regex)?;
+
+    for path in files {
+        let result = if path == "-" {
+            tac_stdin(&separator, before, out)
+        } else {
+            File::open(&path)
+                .and_then(|mut file| file.seek(SeekFrom::End(0)).map(|_| file))
+                .and_then(|file| tac(BufReadBacker::new(file), &separator, before, out))
+        };
+
+        result.map_err(|err| format!("{}: {}", path, err))?;
+    }
+
+    out.flush().map_err(|err| err.to_string())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    match run(&args, &mut out) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("tac: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -33,6 +33,9 @@
 //! ```
 mod read_back;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 // Bare metal platforms usually have very small amounts of RAM
 // (in the order of hundreds of KB)
 const DEFAULT_BUF_SIZE: usize = if cfg!(target_os = "espidf") {
@@ -41,6 +44,21 @@ const DEFAULT_BUF_SIZE: usize = if cfg!(target_os = "espidf") {
     8 * 1024
 };
 
+pub use read_back::seek_to_time;
 pub use read_back::{
-    BufReadBack, BufReadBacker, ReadBack, ReadBackBytes, ReadBackChain, ReadBackSplit, ReadBackTake,
+    BorrowedBackBuf, BufReadBack, BufReadBacker, DirectReadBacker, GrepGroup, GrepLine,
+    PrefetchingReadBacker, ReadBack, ReadBackBufBytes, ReadBackBytes, ReadBackChain,
+    ReadBackConcat, ReadBackCounters, ReadBackError, ReadBackGrep, ReadBackLines, ReadBackMatches,
+    ReadBackOperation, ReadBackProgress, ReadBackRecords, ReadBackSplit, ReadBackStats,
+    ReadBackTake, ReadBackUtf16Lines, Record, RecordBounds, RotatedLogReadBack, Utf16Endianness,
+    DEFAULT_MAX_RECORD_LEN, DEFAULT_PREFETCH_SIZE, DEFAULT_PROGRESS_INTERVAL,
 };
+
+#[cfg(feature = "encoding")]
+pub use encoding_rs;
+#[cfg(feature = "encoding")]
+pub use read_back::ReadBackEncodedLines;
+#[cfg(feature = "graphemes")]
+pub use read_back::ReadBackGraphemes;
+#[cfg(feature = "spool")]
+pub use read_back::{SpooledReadBack, DEFAULT_SPOOL_THRESHOLD};
@@ -0,0 +1,250 @@
+use std::{fmt, mem::MaybeUninit};
+
+/// A borrowed buffer which is filled from its end towards its start, for [`ReadBack::read_back_buf`].
+///
+/// It's the read-back version of the unstable [`BorrowedBuf`] of `std`: The buffer may be uninitialised and it
+/// tracks how many bytes at its end are filled and how many are initialised. The filled bytes are always a part
+/// of the initialised bytes:
+///
+/// ```text
+/// [ uninitialised | initialised, unfilled | filled ]
+/// ```
+///
+/// So a reader can read back into uninitialised memory without ever exposing it as `&[u8]`.
+///
+/// # Example
+/// ```
+/// use read_collection::{BorrowedBackBuf, ReadBack};
+/// use std::mem::MaybeUninit;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut data = [1, 2, 3, 4, 5].as_slice();
+///     let mut memory = [MaybeUninit::uninit(); 3];
+///     let mut buf = BorrowedBackBuf::from(memory.as_mut_slice());
+///
+///     data.read_back_buf(&mut buf)?;
+///     assert_eq!(buf.filled(), [3, 4, 5]);
+///     assert_eq!(data, [1, 2]);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`ReadBack::read_back_buf`]: crate::ReadBack::read_back_buf
+/// [`BorrowedBuf`]: https://doc.rust-lang.org/std/io/struct.BorrowedBuf.html
+pub struct BorrowedBackBuf<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    /// The amount of filled bytes at the end.
+    filled: usize,
+    /// The amount of initialised bytes at the end, which is at least `filled`.
+    init: usize,
+}
+
+impl fmt::Debug for BorrowedBackBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedBackBuf")
+            .field("capacity", &self.capacity())
+            .field("filled", &self.filled)
+            .field("init", &self.init)
+            .finish()
+    }
+}
+
+/// Creates a new `BorrowedBackBuf` from a fully initialised slice.
+impl<'a> From<&'a mut [u8]> for BorrowedBackBuf<'a> {
+    fn from(buf: &'a mut [u8]) -> Self {
+        let init = buf.len();
+        // SAFETY: `MaybeUninit<u8>` has the same layout as `u8` and the buffer never de-initialises bytes.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+
+        Self {
+            buf,
+            filled: 0,
+            init,
+        }
+    }
+}
+
+/// Creates a new `BorrowedBackBuf` from an uninitialised slice.
+impl<'a> From<&'a mut [MaybeUninit<u8>]> for BorrowedBackBuf<'a> {
+    fn from(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            buf,
+            filled: 0,
+            init: 0,
+        }
+    }
+}
+
+impl<'a> BorrowedBackBuf<'a> {
+    /// Returns the total size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the amount of filled bytes.
+    pub fn len(&self) -> usize {
+        self.filled
+    }
+
+    /// Returns `true` if no bytes have been filled yet.
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Returns the amount of bytes which can still be filled.
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.filled
+    }
+
+    /// Returns the amount of initialised bytes at the end of the buffer.
+    pub fn init_len(&self) -> usize {
+        self.init
+    }
+
+    /// Returns the filled bytes, which are at the end of the buffer.
+    pub fn filled(&self) -> &[u8] {
+        let start = self.capacity() - self.filled;
+        // SAFETY: The filled bytes are initialised.
+        unsafe { assume_init(&self.buf[start..]) }
+    }
+
+    /// Clears the filled bytes, but keeps them initialised.
+    pub fn clear(&mut self) -> &mut Self {
+        self.filled = 0;
+        self
+    }
+
+    /// Puts `bytes` in front of the filled bytes.
+    ///
+    /// # Panics
+    /// Panics if there are less than `bytes.len()` bytes remaining.
+    pub fn prepend(&mut self, bytes: &[u8]) {
+        assert!(
+            bytes.len() <= self.remaining(),
+            "{} bytes don't fit into the remaining {} bytes",
+            bytes.len(),
+            self.remaining()
+        );
+
+        let end = self.remaining();
+        let start = end - bytes.len();
+        for (dest, byte) in self.buf[start..end].iter_mut().zip(bytes) {
+            dest.write(*byte);
+        }
+
+        self.filled += bytes.len();
+        self.init = std::cmp::max(self.init, self.filled);
+    }
+
+    /// Initialises the unfilled part of the buffer with zeros (if it isn't initialised yet) and returns it.
+    ///
+    /// New bytes belong at the *end* of the returned slice, since they are in front of the filled ones. Mark them
+    /// as filled with [`advance`] afterwards.
+    ///
+    /// [`advance`]: BorrowedBackBuf::advance
+    pub fn init_unfilled(&mut self) -> &mut [u8] {
+        let end = self.remaining();
+        let init_start = self.capacity() - self.init;
+        for byte in &mut self.buf[..init_start] {
+            byte.write(0);
+        }
+        self.init = self.capacity();
+
+        // SAFETY: The whole buffer is initialised now.
+        unsafe { assume_init_mut(&mut self.buf[..end]) }
+    }
+
+    /// Marks the last `n` unfilled bytes as filled.
+    ///
+    /// # Panics
+    /// Panics if these bytes aren't initialised.
+    pub fn advance(&mut self, n: usize) -> &mut Self {
+        assert!(
+            self.filled + n <= self.init,
+            "only {} unfilled bytes are initialised",
+            self.init - self.filled
+        );
+
+        self.filled += n;
+        self
+    }
+
+    /// Marks the last `n` unfilled bytes as filled, without checking that they are initialised.
+    ///
+    /// # Safety
+    /// The last `n` unfilled bytes have to be initialised.
+    pub unsafe fn advance_unchecked(&mut self, n: usize) -> &mut Self {
+        self.filled += n;
+        self.init = std::cmp::max(self.init, self.filled);
+        self
+    }
+
+    /// Returns the unfilled part of the buffer, which may be uninitialised.
+    ///
+    /// # Safety
+    /// The caller mustn't de-initialise any bytes, for example by writing `MaybeUninit::uninit()`.
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let end = self.remaining();
+        &mut self.buf[..end]
+    }
+
+    /// Asserts that the last `n` bytes of the buffer are initialised.
+    ///
+    /// If fewer bytes are initialised already, nothing changes.
+    ///
+    /// # Safety
+    /// The last `n` bytes of the buffer have to be initialised.
+    pub unsafe fn set_init(&mut self, n: usize) -> &mut Self {
+        self.init = std::cmp::max(self.init, n);
+        self
+    }
+}
+
+/// Returns the initialised bytes of `slice`.
+///
+/// # Safety
+/// All bytes of `slice` have to be initialised.
+pub(crate) unsafe fn assume_init(slice: &[MaybeUninit<u8>]) -> &[u8] {
+    // SAFETY: It equals https://doc.rust-lang.org/src/core/mem/maybe_uninit.rs.html#995.
+    unsafe { &*(slice as *const [MaybeUninit<u8>] as *const [u8]) }
+}
+
+/// Same as [`assume_init`] but for mutable slices.
+///
+/// # Safety
+/// All bytes of `slice` have to be initialised.
+unsafe fn assume_init_mut(slice: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+    // SAFETY: See `assume_init`.
+    unsafe { &mut *(slice as *mut [MaybeUninit<u8>] as *mut [u8]) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_from_the_end() {
+        let mut memory = [MaybeUninit::uninit(); 5];
+        let mut buf = BorrowedBackBuf::from(memory.as_mut_slice());
+
+        buf.prepend(&[4, 5]);
+        assert_eq!(buf.filled(), [4, 5]);
+        assert_eq!(buf.init_len(), 2);
+
+        let unfilled = buf.init_unfilled();
+        assert_eq!(unfilled, [0, 0, 0]);
+        unfilled[2] = 3;
+        buf.advance(1);
+
+        assert_eq!(buf.filled(), [3, 4, 5]);
+        assert_eq!(buf.remaining(), 2);
+        assert_eq!(buf.init_len(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn advance_into_uninitialised_bytes() {
+        let mut memory = [MaybeUninit::uninit(); 2];
+        BorrowedBackBuf::from(memory.as_mut_slice()).advance(1);
+    }
+}
@@ -1,13 +1,18 @@
 use std::mem::MaybeUninit;
 
-use crate::ReadBack;
+use crate::{read_back::borrowed_back_buf::assume_init, BorrowedBackBuf, ReadBack};
 
 /// Heavily inspired by the `std` implementation.
+///
+/// The buffered bytes are `buf[pos..filled]`. Since the bytes are read back into the end of the buffer, the
+/// initialised bytes are tracked from the end as well.
 #[derive(Debug)]
 pub struct Buffer {
     buf: Box<[MaybeUninit<u8>]>,
     pos: usize,
     filled: usize,
+    /// The amount of initialised bytes at the end of `buf`.
+    initialized: usize,
 }
 
 // methods which are similar to `BufReader`
@@ -19,13 +24,14 @@ impl Buffer {
             buf,
             pos: 0,
             filled: 0,
+            initialized: 0,
         }
     }
 
     #[inline]
     pub fn buffer(&self) -> &[u8] {
-        // SAFETY: It's guaranteed that everything <= self.filled is initialised and self.pos <= self.filled
-        unsafe { std::mem::transmute(&self.buf[self.pos..self.filled]) }
+        // SAFETY: It's guaranteed that everything in pos..filled is initialised
+        unsafe { assume_init(&self.buf[self.pos..self.filled]) }
     }
 
     #[inline]
@@ -51,30 +57,97 @@ impl Buffer {
         self.filled = 0;
     }
 
+    /// Replaces the buffer with a bigger one, if its capacity is smaller than `capacity`.
+    ///
+    /// The buffer has to be empty.
+    #[inline]
+    pub fn grow(&mut self, capacity: usize) {
+        debug_assert!(self.pos == self.filled);
+
+        if self.capacity() < capacity {
+            *self = Self::with_capacity(capacity);
+        }
+    }
+
+    /// Consumes `amt` bytes from the *end* of the buffered data, since we're reading back.
     #[inline]
     pub fn consume(&mut self, amt: usize) {
-        self.pos = std::cmp::min(self.filled, self.pos + amt);
+        self.filled = std::cmp::max(self.pos, self.filled.saturating_sub(amt));
     }
 
     #[inline]
-    pub fn fill_buf(&mut self, mut reader: impl ReadBack) -> std::io::Result<&[u8]> {
+    pub fn fill_buf(&mut self, reader: impl ReadBack) -> std::io::Result<&[u8]> {
         // If we've reached the end of our internal buffer then we need to fetch
         // some more data from the reader.
         // Branch using `>=` instead of the more correct `==`
-        // to tell the compiler that the pos..cap slice is always valid.
-        if self.filled >= self.pos {
+        // to tell the compiler that the pos..filled slice is always valid.
+        if self.pos >= self.filled {
             debug_assert!(self.pos == self.filled);
 
-            // SAFETY: It equals https://doc.rust-lang.org/src/core/mem/maybe_uninit.rs.html#995.
-            let buffer =
-                unsafe { &mut *(&mut self.buf[..] as *mut [MaybeUninit<u8>] as *mut [u8]) };
+            self.pos = self.capacity();
+            self.filled = self.capacity();
+            self.read_back_in_front(reader)?;
+        }
 
-            self.pos = 0;
-            self.filled = reader.read_back(buffer)?;
+        Ok(self.buffer())
+    }
+
+    /// Reads back until at least `amount` bytes are buffered or the reader is exhausted.
+    ///
+    /// The buffer grows if its capacity is smaller than `amount`.
+    pub fn fill_at_least(
+        &mut self,
+        amount: usize,
+        mut reader: impl ReadBack,
+    ) -> std::io::Result<&[u8]> {
+        let len = self.filled - self.pos;
+        if len >= amount {
+            return Ok(self.buffer());
+        }
+
+        // the new bytes are put in front of the buffered ones, so move them to the end
+        if self.capacity() < amount {
+            let mut buf = vec![MaybeUninit::uninit(); amount].into_boxed_slice();
+            buf[amount - len..].copy_from_slice(&self.buf[self.pos..self.filled]);
+            self.buf = buf;
+            self.initialized = len;
+        } else {
+            let capacity = self.capacity();
+            self.buf.copy_within(self.pos..self.filled, capacity - len);
+            self.initialized = std::cmp::max(self.initialized, len);
+        }
+        self.filled = self.capacity();
+        self.pos = self.filled - len;
+
+        while self.filled - self.pos < amount {
+            match self.read_back_in_front(&mut reader) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
         }
 
         Ok(self.buffer())
     }
+
+    /// Reads back into the free space in front of the buffered bytes, which are kept at the end of `buf`.
+    fn read_back_in_front(&mut self, mut reader: impl ReadBack) -> std::io::Result<usize> {
+        debug_assert!(self.filled == self.capacity());
+
+        let capacity = self.capacity();
+        let mut buf = BorrowedBackBuf::from(&mut self.buf[..self.pos]);
+        // SAFETY: `self.buf[capacity - initialized..]` is initialised and so are the buffered bytes behind `pos`.
+        unsafe { buf.set_init((self.initialized + self.pos).saturating_sub(capacity)) };
+
+        // like in `std`, the bytes which have been read back before an error are kept
+        let result = reader.read_back_buf(&mut buf);
+        let (read, init) = (buf.len(), buf.init_len());
+        self.initialized = std::cmp::max(self.initialized, capacity - self.pos + init);
+        self.pos -= read;
+
+        result.map(|()| read)
+    }
 }
 
 #[cfg(test)]
@@ -96,7 +169,50 @@ mod tests {
         let mut buffer = Buffer::with_capacity(5);
 
         assert_eq!(buffer.fill_buf(data).ok(), Some(data));
-        assert_eq!(buffer.pos, 0);
-        assert_eq!(buffer.filled, data.len());
+        // the bytes are read back into the end of the buffer
+        assert_eq!(buffer.pos, 2);
+        assert_eq!(buffer.filled, 5);
+        assert_eq!(buffer.initialized, data.len());
+    }
+
+    #[test]
+    fn consume_from_the_back() {
+        let data: &[u8] = &[1, 2, 3];
+        let mut buffer = Buffer::with_capacity(5);
+
+        buffer.fill_buf(data).unwrap();
+        buffer.consume(1);
+        assert_eq!(buffer.buffer(), &[1, 2]);
+
+        // there's still data left, so the reader mustn't be touched
+        assert_eq!(
+            buffer.fill_buf([4u8, 5].as_slice()).ok(),
+            Some([1u8, 2].as_slice())
+        );
+
+        buffer.consume(10);
+        assert!(buffer.buffer().is_empty());
+    }
+
+    #[test]
+    fn fill_at_least() {
+        let data: &[u8] = &[1, 2, 3, 4, 5, 6, 7];
+        let mut reader = crate::testing::ShortReadBack::new(data, 2);
+        let mut buffer = Buffer::with_capacity(4);
+
+        assert_eq!(buffer.fill_buf(&mut reader).ok(), Some([6, 7].as_slice()));
+        buffer.consume(1);
+
+        // keeps the buffered bytes and grows
+        assert_eq!(
+            buffer.fill_at_least(5, &mut reader).ok(),
+            Some([2, 3, 4, 5, 6].as_slice())
+        );
+        assert_eq!(buffer.capacity(), 5);
+
+        assert_eq!(
+            buffer.fill_at_least(10, &mut reader).ok(),
+            Some([1, 2, 3, 4, 5, 6].as_slice())
+        );
     }
 }
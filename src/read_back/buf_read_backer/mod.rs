@@ -1,8 +1,11 @@
 mod buffer;
 
-use std::io::BufReader;
+use std::io::{BufReader, IoSliceMut, Seek};
 
-use crate::{BufReadBack, ReadBack, DEFAULT_BUF_SIZE};
+use crate::{
+    read_back::add_size_hints, BorrowedBackBuf, BufReadBack, ReadBack, ReadBackMatches,
+    DEFAULT_BUF_SIZE,
+};
 
 use self::buffer::Buffer;
 
@@ -37,6 +40,15 @@ use self::buffer::Buffer;
 pub struct BufReadBacker<R> {
     inner: R,
     buf: Buffer,
+    readahead: Option<Readahead>,
+}
+
+/// The state of the adaptive readahead, see [`BufReadBacker::with_adaptive_readahead`].
+#[derive(Debug)]
+struct Readahead {
+    /// The capacity for the next refill.
+    next: usize,
+    max: usize,
 }
 
 impl<R> BufReadBacker<R> {
@@ -190,8 +202,132 @@ impl<R: ReadBack> BufReadBacker<R> {
         Self {
             inner,
             buf: Buffer::with_capacity(capacity),
+            readahead: None,
         }
     }
+
+    /// Grows the read size while the reader is read back, up to `max_capacity` bytes.
+    ///
+    /// Each refill of the buffer reads twice as much as the one before, so long backward scans need fewer system
+    /// calls while short reads at the end stay cheap. After each refill, the inner reader is advised to fetch the
+    /// bytes of the next refill with [`read_back_advise`], which lets a [`File`] ask the kernel to read them ahead
+    /// on Linux. No advice is given once the [`read_back_size_hint`] of the inner reader says that it has reached
+    /// its start.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, BufReadBacker};
+    /// use std::io::{Seek, SeekFrom, Write};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = tempfile::tempfile()?;
+    ///     file.write_all(&[b'a'; 100])?;
+    ///     file.seek(SeekFrom::End(0))?;
+    ///
+    ///     let mut reader = BufReadBacker::with_capacity(8, file).with_adaptive_readahead(32);
+    ///     for expected in [8, 16, 32, 32] {
+    ///         let len = reader.read_back_fill_buf()?.len();
+    ///         assert_eq!(len, expected);
+    ///         reader.read_back_consume(len);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`read_back_advise`]: ReadBack::read_back_advise
+    /// [`read_back_size_hint`]: ReadBack::read_back_size_hint
+    /// [`File`]: std::fs::File
+    pub fn with_adaptive_readahead(mut self, max_capacity: usize) -> Self {
+        let capacity = self.capacity();
+        self.readahead = Some(Readahead {
+            next: capacity,
+            max: std::cmp::max(capacity, max_capacity),
+        });
+        self
+    }
+}
+
+impl<R: ReadBack> BufReadBacker<R> {
+    /// Returns the last `amount` bytes in front of the current position without consuming them.
+    ///
+    /// Unlike [read_back_fill_buf], which may return fewer bytes, this reads back until `amount` bytes are
+    /// buffered and grows the internal buffer if its capacity is too small. Fewer bytes are only returned if the
+    /// start of the reader has been reached.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, BufReadBacker};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let data = b"record\x00\x01TRL";
+    ///     let mut reader = BufReadBacker::with_capacity(2, data.as_slice());
+    ///
+    ///     // decide how to parse the trailer before consuming it
+    ///     assert_eq!(reader.peek_back(3)?, b"TRL");
+    ///     assert_eq!(reader.peek_back(5)?, b"\x00\x01TRL");
+    ///
+    ///     reader.read_back_consume(5);
+    ///     assert_eq!(reader.peek_back(100)?, b"record");
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [read_back_fill_buf]: BufReadBack::read_back_fill_buf
+    pub fn peek_back(&mut self, amount: usize) -> std::io::Result<&[u8]> {
+        let buffer = self.buf.fill_at_least(amount, &mut self.inner)?;
+        Ok(&buffer[buffer.len().saturating_sub(amount)..])
+    }
+}
+
+impl<R: ReadBack + Seek> BufReadBacker<R> {
+    /// Returns the position in the source in front of which the bytes are read back, taking the buffered bytes
+    /// into account.
+    pub fn position(&mut self) -> std::io::Result<u64> {
+        Ok(self.inner.stream_position()? + self.buffer().len() as u64)
+    }
+
+    /// Searches backwards for the last occurrence of `needle` in front of the current position, like
+    /// [`rfind`], but returns the absolute offset of the occurrence in the source.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, BufReadBacker};
+    /// use std::io::{Seek, SeekFrom, Write};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = tempfile::tempfile()?;
+    ///     file.write_all(b"ok\nERROR: a\nok\nERROR: b\n")?;
+    ///     file.seek(SeekFrom::Start(12))?;
+    ///
+    ///     let mut reader = BufReadBacker::new(file);
+    ///     assert_eq!(reader.rfind_offset(b"ERROR")?, Some(3));
+    ///     assert_eq!(reader.position()?, 3);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the errors of [`rfind`] and the error if the position of the source can't be determined.
+    ///
+    /// [`rfind`]: BufReadBack::rfind
+    pub fn rfind_offset(&mut self, needle: &[u8]) -> std::io::Result<Option<u64>> {
+        let pos = self.position()?;
+        Ok(self.rfind(needle)?.map(|distance| pos - distance))
+    }
+
+    /// Returns an iterator over the non-overlapping occurrences of `needle` in front of the current position, like
+    /// [`rfind_iter`], but with the absolute offsets of the occurrences in the source.
+    ///
+    /// # Errors
+    /// Returns the error if the position of the source can't be determined. The iterator returns the errors of
+    /// [`rfind`].
+    ///
+    /// [`rfind`]: BufReadBack::rfind
+    /// [`rfind_iter`]: BufReadBack::rfind_iter
+    pub fn rfind_offsets(mut self, needle: &[u8]) -> std::io::Result<ReadBackMatches<Self>> {
+        let pos = self.position()?;
+        Ok(self.rfind_iter(needle).with_position(pos))
+    }
 }
 
 impl<R: ReadBack> ReadBack for BufReadBacker<R> {
@@ -206,11 +342,66 @@ impl<R: ReadBack> ReadBack for BufReadBacker<R> {
         self.read_back_consume(nread);
         Ok(nread)
     }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+        let total_len = bufs.iter().map(|b| b.len()).sum::<usize>();
+        if self.buf.pos() == self.buf.filled() && total_len >= self.capacity() {
+            self.discard_buffer();
+            return self.inner.read_back_vectored(bufs);
+        }
+
+        let mut rem = self.read_back_fill_buf()?;
+        let nread = rem.read_back_vectored(bufs)?;
+        self.read_back_consume(nread);
+        Ok(nread)
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        self.inner.is_read_back_vectored()
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        if self.buf.pos() == self.buf.filled() && buf.remaining() >= self.capacity() {
+            self.discard_buffer();
+            return self.inner.read_back_buf(buf);
+        }
+
+        let mut rem = self.read_back_fill_buf()?;
+        let filled = buf.len();
+        rem.read_back_buf(buf)?;
+        self.read_back_consume(buf.len() - filled);
+        Ok(())
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.buffer().len();
+        add_size_hints((buffered, Some(buffered)), self.inner.read_back_size_hint())
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        // the buffered bytes are behind the position of the inner reader
+        self.inner
+            .read_back_advise(amount.saturating_sub(self.buffer().len()))
+    }
 }
 
 impl<R: ReadBack> BufReadBack for BufReadBacker<R> {
     fn read_back_fill_buf(&mut self) -> std::io::Result<&[u8]> {
-        self.buf.fill_buf(&mut self.inner)
+        let readahead = match &mut self.readahead {
+            Some(readahead) if self.buf.pos() >= self.buf.filled() => readahead,
+            _ => return self.buf.fill_buf(&mut self.inner),
+        };
+
+        self.buf.grow(readahead.next);
+        let read = self.buf.fill_buf(&mut self.inner)?.len();
+        if read > 0 {
+            readahead.next = std::cmp::min(readahead.next.saturating_mul(2), readahead.max);
+            // there's nothing to fetch in front of the start
+            if self.inner.read_back_size_hint() != (0, Some(0)) {
+                self.inner.read_back_advise(readahead.next);
+            }
+        }
+        Ok(self.buf.buffer())
     }
 
     fn read_back_consume(&mut self, amt: usize) {
@@ -252,4 +443,194 @@ mod tests {
         assert_eq!(buf_reader.read_back(&mut buffer).ok(), Some(2));
         assert_eq!(buffer, data[..2]);
     }
+
+    #[test]
+    fn smaller_buffer_than_capacity() {
+        let data: [u8; 6] = [1, 2, 3, 4, 5, 6];
+        let mut buffer: [u8; 2] = [0; 2];
+
+        let mut buf_reader = BufReadBacker::with_capacity(4, data.as_slice());
+
+        assert_eq!(buf_reader.read_back(&mut buffer).ok(), Some(2));
+        assert_eq!(buffer, [5, 6]);
+
+        assert_eq!(buf_reader.read_back(&mut buffer).ok(), Some(2));
+        assert_eq!(buffer, [3, 4]);
+
+        assert_eq!(buf_reader.read_back(&mut buffer).ok(), Some(2));
+        assert_eq!(buffer, [1, 2]);
+
+        assert_eq!(buf_reader.read_back(&mut buffer).ok(), Some(0));
+    }
+
+    #[test]
+    fn read_back_until_over_multiple_refills() {
+        let data = b"first line\nsecond line";
+        let mut buffer = Vec::new();
+
+        let mut buf_reader = BufReadBacker::with_capacity(3, data.as_slice());
+
+        assert_eq!(
+            buf_reader.read_back_until(b'\n', &mut buffer).ok(),
+            Some(12)
+        );
+        assert_eq!(buffer, b"\nsecond line");
+
+        buffer.clear();
+        assert_eq!(
+            buf_reader.read_back_until(b'\n', &mut buffer).ok(),
+            Some(10)
+        );
+        assert_eq!(buffer, b"first line");
+    }
+
+    #[test]
+    fn read_back_to_end_over_multiple_refills() {
+        let data: Vec<u8> = (0..=u8::MAX)
+            .cycle()
+            .take(3 * DEFAULT_BUF_SIZE + 7)
+            .collect();
+        let mut buffer = vec![42];
+
+        let mut buf_reader = BufReadBacker::with_capacity(5, data.as_slice());
+        // leave some bytes in the internal buffer which have to be returned first
+        let mut small_buffer = [0u8; 1];
+        buf_reader.read_back(&mut small_buffer).unwrap();
+        buffer.insert(0, small_buffer[0]);
+
+        assert_eq!(
+            buf_reader.read_back_to_end(&mut buffer).ok(),
+            Some(data.len() - 1)
+        );
+        assert_eq!(&buffer[..data.len()], data.as_slice());
+        assert_eq!(buffer[data.len()], 42);
+    }
+
+    #[test]
+    fn peek_back_keeps_the_bytes() {
+        let data: Vec<u8> = (0..100).collect();
+        let mut buf_reader = BufReadBacker::with_capacity(8, data.as_slice());
+
+        assert_eq!(buf_reader.peek_back(30).unwrap(), &data[70..]);
+        assert_eq!(buf_reader.peek_back(1).unwrap(), &data[99..]);
+        assert_eq!(buf_reader.capacity(), 30);
+
+        let mut buffer = Vec::new();
+        assert_eq!(buf_reader.read_back_to_end(&mut buffer).ok(), Some(100));
+        assert_eq!(buffer, data);
+        assert!(buf_reader.peek_back(1).unwrap().is_empty());
+    }
+
+    /// A reader which records the sizes of the reads and the advice it gets, together with its position.
+    struct CountingReadBack<'a> {
+        data: &'a [u8],
+        reads: Vec<usize>,
+        advice: std::cell::RefCell<Vec<(usize, usize)>>,
+    }
+
+    impl ReadBack for CountingReadBack<'_> {
+        fn read_back(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.reads.push(buf.len());
+            self.data.read_back(buf)
+        }
+
+        fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+            self.data.read_back_size_hint()
+        }
+
+        fn read_back_advise(&self, amount: usize) {
+            self.advice.borrow_mut().push((self.data.len(), amount));
+        }
+    }
+
+    /// Reads back chunk by chunk, so every byte goes through the buffer.
+    fn read_back_all<R: ReadBack>(reader: &mut BufReadBacker<R>) -> Vec<u8> {
+        let mut content = Vec::new();
+        loop {
+            let chunk = reader.read_back_fill_buf().unwrap();
+            if chunk.is_empty() {
+                return content;
+            }
+            content.splice(0..0, chunk.iter().copied());
+            let len = chunk.len();
+            reader.read_back_consume(len);
+        }
+    }
+
+    #[test]
+    fn adaptive_readahead() {
+        let data = [0; 100];
+        let inner = CountingReadBack {
+            data: data.as_slice(),
+            reads: Vec::new(),
+            advice: Default::default(),
+        };
+
+        let mut buf_reader = BufReadBacker::with_capacity(4, inner).with_adaptive_readahead(16);
+        read_back_all(&mut buf_reader);
+
+        let inner = buf_reader.into_inner();
+        assert_eq!(inner.reads, [4, 8, 16, 16, 16, 16, 16, 16, 16]);
+        assert_eq!(
+            inner.advice.into_inner(),
+            [
+                (96, 8),
+                (88, 16),
+                (72, 16),
+                (56, 16),
+                (40, 16),
+                (24, 16),
+                (8, 16)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_readahead_by_default() {
+        let data = [0; 10];
+        let inner = CountingReadBack {
+            data: data.as_slice(),
+            reads: Vec::new(),
+            advice: Default::default(),
+        };
+
+        let mut buf_reader = BufReadBacker::with_capacity(4, inner);
+        read_back_all(&mut buf_reader);
+
+        let inner = buf_reader.into_inner();
+        assert_eq!(inner.reads, [4, 4, 4, 4]);
+        assert!(inner.advice.into_inner().is_empty());
+    }
+
+    #[test]
+    fn adaptive_readahead_of_a_file() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&data).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+
+        let mut buf_reader = BufReadBacker::with_capacity(16, file).with_adaptive_readahead(4096);
+        assert_eq!(read_back_all(&mut buf_reader), data);
+        assert_eq!(buf_reader.capacity(), 4096);
+    }
+
+    #[test]
+    fn bytes_of_a_file() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&[1, 2, 3, 4, 5]).unwrap();
+        file.seek(SeekFrom::Start(4)).unwrap();
+
+        let mut bytes = file.read_back_bytes_buffered();
+        assert_eq!(bytes.size_hint(), (4, Some(4)));
+
+        assert_eq!(bytes.next().and_then(|b| b.ok()), Some(4));
+        assert_eq!(bytes.size_hint(), (3, Some(3)));
+
+        let rest = bytes.collect::<std::io::Result<Vec<u8>>>().unwrap();
+        assert_eq!(rest, [3, 2, 1]);
+    }
 }
@@ -0,0 +1,441 @@
+use std::{
+    alloc::{self, Layout},
+    fmt,
+    fs::File,
+    io::{self, ErrorKind, Seek},
+    path::Path,
+    ptr::NonNull,
+};
+
+use crate::{
+    read_back::impls::{truncated, PositionalRead},
+    BufReadBack, ReadBack,
+};
+
+/// The default amount of bytes which are read at once by a [`DirectReadBacker`], rounded up to whole blocks.
+const DEFAULT_DIRECT_SIZE: usize = 256 * 1024;
+
+/// The smallest block size which is used for the alignment.
+const MIN_BLOCK_SIZE: usize = 512;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod direct {
+    use std::{fs::File, io, os::unix::io::AsRawFd};
+
+    /// Sets or clears `O_DIRECT`. Returns whether the flags have been changed.
+    pub fn set(file: &File, direct: bool) -> io::Result<bool> {
+        let fd = file.as_raw_fd();
+        // SAFETY: `F_GETFL` doesn't access any memory of this process.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let new_flags = if direct {
+            flags | libc::O_DIRECT
+        } else {
+            flags & !libc::O_DIRECT
+        };
+        if new_flags == flags {
+            return Ok(false);
+        }
+
+        // SAFETY: `F_SETFL` doesn't access any memory of this process.
+        if unsafe { libc::fcntl(fd, libc::F_SETFL, new_flags) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(true)
+    }
+
+    /// Whether a read failed because the file system doesn't support `O_DIRECT` for it.
+    pub fn is_unsupported(err: &io::Error) -> bool {
+        err.raw_os_error() == Some(libc::EINVAL)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod direct {
+    use std::{fs::File, io};
+
+    pub fn set(_file: &File, _direct: bool) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    pub fn is_unsupported(_err: &io::Error) -> bool {
+        false
+    }
+}
+
+#[cfg(unix)]
+fn block_size(file: &File) -> io::Result<usize> {
+    use std::os::unix::fs::MetadataExt;
+
+    let block_size = usize::try_from(file.metadata()?.blksize()).unwrap_or(MIN_BLOCK_SIZE);
+    Ok(block_size.max(MIN_BLOCK_SIZE).next_power_of_two())
+}
+
+#[cfg(not(unix))]
+fn block_size(_file: &File) -> io::Result<usize> {
+    Ok(4096)
+}
+
+/// A zeroed buffer whose start is aligned to a block.
+struct AlignedBuf {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// SAFETY: `AlignedBuf` owns its memory like a `Box<[u8]>`.
+unsafe impl Send for AlignedBuf {}
+// SAFETY: See above.
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    /// Allocates `len` bytes aligned to `align`, which has to be a power of two. `len` mustn't be zero.
+    fn new(len: usize, align: usize) -> Self {
+        let layout = Layout::from_size_align(len, align).expect("the buffer is too big");
+        debug_assert!(layout.size() > 0);
+
+        // SAFETY: The size of the layout isn't zero.
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        Self { ptr, layout }
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: The memory is initialised and belongs to `self`.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.layout.size()) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: The memory is initialised and belongs to `self`.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        // SAFETY: The memory has been allocated with the same layout.
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/// Reads a [`File`] back in whole blocks, bypassing the page cache where possible.
+///
+/// Each read starts and ends at a block boundary of the file system and goes into a buffer which is aligned to a
+/// block, which is what `O_DIRECT` requires. On Linux, `O_DIRECT` is enabled for the file, so the data doesn't
+/// pollute the page cache. If the file system doesn't support it (like `tmpfs` on older kernels), it falls back
+/// to normal reads, see [`is_direct`].
+///
+/// The blocks usually cover more than the bytes in front of the current position, because the position doesn't
+/// need to be aligned. Only the bytes in front of the position are returned through [`BufReadBack`], the rest of
+/// the last block is skipped.
+///
+/// The reader uses positional reads, so the position of the file itself isn't changed.
+///
+/// # Example
+/// ```
+/// use read_collection::{BufReadBack, DirectReadBacker};
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = tempfile::NamedTempFile::new()?;
+///     file.write_all(b"first\nsecond\nthird")?;
+///
+///     let reader = DirectReadBacker::open(file.path())?;
+///     let lines = reader.read_back_lines().collect::<std::io::Result<Vec<String>>>()?;
+///
+///     assert_eq!(lines, ["third", "second", "first"]);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`is_direct`]: DirectReadBacker::is_direct
+pub struct DirectReadBacker {
+    file: File,
+    buf: AlignedBuf,
+    block_size: usize,
+    /// The offset in the file of the first byte of `buf`.
+    buf_start: u64,
+    /// The amount of bytes at the start of `buf` which have been read from the file.
+    buf_len: usize,
+    /// The position in front of which nothing has been read back yet.
+    pos: u64,
+    /// Whether `O_DIRECT` is enabled by this reader and supported by the file system.
+    direct: bool,
+    /// Whether `O_DIRECT` has been enabled by this reader, so it has to be cleared again.
+    enabled_direct: bool,
+}
+
+impl fmt::Debug for DirectReadBacker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectReadBacker")
+            .field("file", &self.file)
+            .field("capacity", &self.capacity())
+            .field("block_size", &self.block_size)
+            .field("pos", &self.pos)
+            .field("direct", &self.direct)
+            .finish()
+    }
+}
+
+impl DirectReadBacker {
+    /// Opens the file at `path` and reads it back from its end.
+    ///
+    /// # Errors
+    /// Returns the error if the file can't be opened or its metadata can't be read.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Self::at(file, len)
+    }
+
+    /// Creates a new `DirectReadBacker` which reads `file` back from its current position.
+    ///
+    /// # Errors
+    /// Returns the error if the position or the metadata of the file can't be read.
+    pub fn new(mut file: File) -> io::Result<Self> {
+        let pos = file.stream_position()?;
+        Self::at(file, pos)
+    }
+
+    fn at(file: File, pos: u64) -> io::Result<Self> {
+        let block_size = block_size(&file)?;
+        let enabled_direct = direct::set(&file, true).unwrap_or(false);
+
+        let mut reader = Self {
+            file,
+            buf: AlignedBuf::new(block_size, block_size),
+            block_size,
+            buf_start: 0,
+            buf_len: 0,
+            pos,
+            direct: enabled_direct,
+            enabled_direct,
+        };
+        reader.set_capacity(DEFAULT_DIRECT_SIZE);
+        Ok(reader)
+    }
+
+    /// Reads up to `capacity` bytes at once, rounded up to whole blocks.
+    ///
+    /// The buffered bytes are discarded, so it should be called before reading back.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::DirectReadBacker;
+    /// use std::io::Write;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = tempfile::NamedTempFile::new()?;
+    ///     file.write_all(b"content")?;
+    ///
+    ///     let reader = DirectReadBacker::open(file.path())?.with_capacity(1);
+    ///     assert_eq!(reader.capacity(), reader.block_size());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.set_capacity(capacity);
+        self
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        let blocks = std::cmp::max(capacity.div_ceil(self.block_size), 1);
+        self.buf = AlignedBuf::new(blocks * self.block_size, self.block_size);
+        self.buf_len = 0;
+    }
+
+    /// Returns the amount of bytes which are read at once.
+    pub fn capacity(&self) -> usize {
+        self.buf.as_slice().len()
+    }
+
+    /// Returns the size of the blocks which the reads are aligned to.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns whether this reader has enabled `O_DIRECT` for the file, so the reads bypass the page cache.
+    ///
+    /// It's `false` on other systems than Linux and if the file system doesn't support `O_DIRECT`.
+    pub fn is_direct(&self) -> bool {
+        self.direct
+    }
+
+    /// Returns the position in front of which nothing has been read back yet.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns a reference to the internally buffered data.
+    pub fn buffer(&self) -> &[u8] {
+        let buffered = self.buf_start..self.buf_start + self.buf_len as u64;
+        if self.pos > buffered.start && self.pos <= buffered.end {
+            &self.buf.as_slice()[..(self.pos - self.buf_start) as usize]
+        } else {
+            &[]
+        }
+    }
+
+    /// Unwraps this `DirectReadBacker`, returning the file.
+    ///
+    /// `O_DIRECT` is cleared again if it has been enabled by this reader. The position of the file hasn't been
+    /// changed, see [`position`] for the position of the reader.
+    ///
+    /// [`position`]: DirectReadBacker::position
+    pub fn into_inner(self) -> File {
+        if self.enabled_direct && self.direct {
+            let _ = direct::set(&self.file, false);
+        }
+        self.file
+    }
+
+    /// Reads the aligned blocks around the bytes in front of the position.
+    fn refill(&mut self) -> io::Result<()> {
+        let block_size = self.block_size as u64;
+        let end = self.pos.div_ceil(block_size) * block_size;
+        let start = end.saturating_sub(self.capacity() as u64);
+
+        // the last block may reach behind the end of the file, so the last read can be shorter
+        let len = (end - start) as usize;
+        let read = self.read_at(start, len)?;
+        if start + (read as u64) < self.pos {
+            return Err(truncated());
+        }
+
+        self.buf_start = start;
+        self.buf_len = read;
+        Ok(())
+    }
+
+    /// Reads up to `len` bytes from `offset` on into the start of the buffer.
+    fn read_at(&mut self, offset: u64, len: usize) -> io::Result<usize> {
+        let mut read = 0;
+        while read < len {
+            match self.file.read_at(
+                &mut self.buf.as_mut_slice()[read..len],
+                offset + read as u64,
+            ) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                // some file systems accept `O_DIRECT` when opening, but not when reading
+                Err(err) if self.direct && direct::is_unsupported(&err) => {
+                    direct::set(&self.file, false)?;
+                    self.direct = false;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl ReadBack for DirectReadBacker {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut rem = self.read_back_fill_buf()?;
+        let nread = rem.read_back(buf)?;
+        self.read_back_consume(nread);
+        Ok(nread)
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.pos) {
+            Ok(pos) => (pos, Some(pos)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+impl BufReadBack for DirectReadBacker {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos > 0 && self.buffer().is_empty() {
+            self.refill()?;
+        }
+        Ok(self.buffer())
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        let amt = std::cmp::min(amt, self.buffer().len());
+        self.pos -= amt as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use std::io::{SeekFrom, Write};
+
+    fn file(content: &[u8]) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content).unwrap();
+        file
+    }
+
+    #[test]
+    fn conformance() {
+        testing::check_buf_read_back_with_config(
+            proptest::test_runner::Config::with_cases(64),
+            |content| Box::new(DirectReadBacker::new(file(content)).unwrap()),
+        );
+    }
+
+    #[test]
+    fn unaligned_positions() {
+        let content: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+
+        for pos in [0, 1, 511, 4096, 4097, 12_345, 20_000] {
+            let mut file = file(&content);
+            file.seek(SeekFrom::Start(pos)).unwrap();
+
+            let mut reader = DirectReadBacker::new(file).unwrap().with_capacity(1);
+            let block_size = reader.block_size();
+            let mut read = Vec::new();
+            loop {
+                let chunk = reader.read_back_fill_buf().unwrap();
+                if chunk.is_empty() {
+                    break;
+                }
+                assert!(chunk.len() <= block_size);
+                read.splice(0..0, chunk.iter().copied());
+                let len = chunk.len();
+                reader.read_back_consume(len);
+            }
+
+            assert_eq!(read, &content[..pos as usize], "position: {}", pos);
+        }
+    }
+
+    #[test]
+    fn open_reads_from_the_end() {
+        let content = b"some content which isn't a whole block";
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content).unwrap();
+
+        let mut reader = DirectReadBacker::open(file.path()).unwrap();
+        let mut read = Vec::new();
+        reader.read_back_to_end(&mut read).unwrap();
+        assert_eq!(read, content);
+        assert_eq!(reader.position(), 0);
+
+        // the flags are restored, so unaligned reads work again
+        let mut file = reader.into_inner();
+        let mut read = Vec::new();
+        io::Read::read_to_end(&mut file, &mut read).unwrap();
+        assert_eq!(read, content);
+    }
+
+    #[test]
+    fn truncated_file() {
+        let file = file(&[0; 10_000]);
+        let mut reader = DirectReadBacker::new(file.try_clone().unwrap()).unwrap();
+        file.set_len(100).unwrap();
+
+        assert_eq!(
+            reader.read_back_fill_buf().unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+    }
+}
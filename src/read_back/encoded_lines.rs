@@ -0,0 +1,255 @@
+use std::io::{self, ErrorKind};
+
+use encoding_rs::{DecoderResult, Encoding};
+
+use crate::{BufReadBack, ReadBackError, ReadBackOperation};
+
+/// An iterator over the lines of an instance of [`BufReadBack`] in a legacy encoding, transcoded to UTF-8 and
+/// starting with the last line.
+///
+/// This struct is generally created by calling [`read_back_encoded_lines`] on a [`BufReadBack`].
+/// Please see the documentation of [`read_back_encoded_lines`] for more details.
+///
+/// [`read_back_encoded_lines`]: BufReadBack::read_back_encoded_lines
+#[derive(Debug)]
+pub struct ReadBackEncodedLines<B> {
+    buf: B,
+    encoding: &'static Encoding,
+    /// `true` if a new line has been read back whose line hasn't been returned yet.
+    pending_line: bool,
+    started: bool,
+    /// The amount of read-back bytes, for the offsets of errors.
+    amount_read: u64,
+}
+
+impl<B> ReadBackEncodedLines<B> {
+    pub(crate) fn new(buf: B, encoding: &'static Encoding) -> io::Result<Self> {
+        // In ASCII compatible encodings, the `\n` byte can't be part of a multi-byte sequence (the trail bytes of
+        // Shift_JIS, EUC-JP, EUC-KR, Big5 and gb18030 are all greater than it), so every `\n` byte is a new line.
+        // That doesn't hold for UTF-16 and the escape sequences of ISO-2022-JP depend on the bytes in front of them.
+        if !encoding.is_ascii_compatible() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} can't be read back line by line, since its new lines are ambiguous when reading backwards",
+                    encoding.name()
+                ),
+            ));
+        }
+
+        Ok(Self {
+            buf,
+            encoding,
+            pending_line: false,
+            started: false,
+            amount_read: 0,
+        })
+    }
+
+    /// Returns the encoding of the lines.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+
+    fn decode(&self, mut line: &[u8], at_start: bool) -> io::Result<String> {
+        // the line starts behind its new line
+        let line_len = self.amount_read - u64::from(!at_start);
+
+        if line.last() == Some(&b'\r') {
+            line = &line[..line.len() - 1];
+        }
+        // the byte order mark is only at the start of the source
+        let mut content_start = 0;
+        if at_start {
+            if let Some((encoding, bom_len)) = Encoding::for_bom(line) {
+                if encoding == self.encoding {
+                    line = &line[bom_len..];
+                    content_start = bom_len as u64;
+                }
+            }
+        }
+
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(line)
+            .map(String::from)
+            .ok_or_else(|| {
+                let offset = content_start + malformed_index(self.encoding, line) as u64;
+                ReadBackError::invalid_text(
+                    ReadBackOperation::ReadBackEncodedLines,
+                    self.encoding.name(),
+                    line_len - offset,
+                    at_start.then_some(offset),
+                )
+                .into()
+            })
+    }
+}
+
+/// Returns the index of the first malformed byte sequence of `line`.
+fn malformed_index(encoding: &'static Encoding, line: &[u8]) -> usize {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut output = [0; 1024];
+    let mut read = 0;
+
+    loop {
+        let (result, amount, _) =
+            decoder.decode_to_utf8_without_replacement(&line[read..], &mut output, true);
+        read += amount;
+
+        match result {
+            DecoderResult::Malformed(malformed, after) => {
+                return read - usize::from(malformed) - usize::from(after)
+            }
+            DecoderResult::OutputFull => (),
+            DecoderResult::InputEmpty => return line.len(),
+        }
+    }
+}
+
+impl<B: BufReadBack> Iterator for ReadBackEncodedLines<B> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        let mut line = Vec::new();
+
+        loop {
+            match self.buf.read_back_until(b'\n', &mut line) {
+                Ok(0) if self.pending_line => {
+                    self.pending_line = false;
+                    return Some(Ok(String::new()));
+                }
+                Ok(0) => return None,
+                Ok(amount) => self.amount_read += amount as u64,
+                Err(err) => return Some(Err(err)),
+            }
+
+            // the new line in front of the content terminates the previous line
+            let has_new_line = line[0] == b'\n';
+            let content = if has_new_line { &line[1..] } else { &line[..] };
+
+            // like `BufRead::lines`: a new line at the end of the source doesn't start another line
+            if !self.started && content.is_empty() {
+                self.started = true;
+                self.pending_line = true;
+                line.clear();
+                continue;
+            }
+
+            self.started = true;
+            self.pending_line = has_new_line;
+            return Some(self.decode(content, !has_new_line));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufReadBacker;
+    use encoding_rs::{ISO_2022_JP, SHIFT_JIS, UTF_16LE, UTF_8, WINDOWS_1252};
+
+    fn lines(data: &[u8], encoding: &'static Encoding, capacity: usize) -> Vec<String> {
+        BufReadBacker::with_capacity(capacity, data)
+            .read_back_encoded_lines(encoding)
+            .unwrap()
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_as_lines() {
+        let texts = [
+            "",
+            "\n",
+            "\n\n",
+            "one line",
+            "first\nsecond\n",
+            "first\r\n\r\nthird",
+        ];
+
+        for text in texts {
+            let expected: Vec<String> = text.lines().rev().map(String::from).collect();
+
+            for capacity in [1, 3, 64] {
+                assert_eq!(
+                    lines(text.as_bytes(), WINDOWS_1252, capacity),
+                    expected,
+                    "text: {:?}, capacity: {}",
+                    text,
+                    capacity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn windows_1252() {
+        let (data, _, _) = WINDOWS_1252.encode("café\n€ 5");
+        assert_eq!(lines(&data, WINDOWS_1252, 2), ["€ 5", "café"]);
+    }
+
+    #[test]
+    fn shift_jis() {
+        // "表" is encoded as 0x95 0x5C, whose trail byte is a backslash
+        let text = "日本語\r\n表示\nテスト";
+        let (data, _, _) = SHIFT_JIS.encode(text);
+
+        assert_eq!(lines(&data, SHIFT_JIS, 3), ["テスト", "表示", "日本語"]);
+    }
+
+    #[test]
+    fn strips_bom() {
+        assert_eq!(
+            lines(b"\xEF\xBB\xBFfirst\nsecond", UTF_8, 4),
+            ["second", "first"]
+        );
+    }
+
+    #[test]
+    fn invalid_line() {
+        let data = b"\x82\n\x82\xA0";
+        let mut lines = data.as_slice().read_back_encoded_lines(SHIFT_JIS).unwrap();
+
+        assert_eq!(lines.next().unwrap().ok(), Some("あ".to_string()));
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(
+            err.map(|e| e.operation()),
+            Some(ReadBackOperation::ReadBackEncodedLines)
+        );
+        assert_eq!(err.map(|e| e.encoding()), Some("Shift_JIS"));
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(4));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(0)));
+    }
+
+    #[test]
+    fn invalid_line_behind_bom() {
+        let data = b"\xEF\xBB\xBFok \xFF\r\nlast";
+        let mut lines = BufReadBacker::with_capacity(2, data.as_slice())
+            .read_back_encoded_lines(UTF_8)
+            .unwrap();
+
+        assert_eq!(lines.next().unwrap().ok(), Some("last".to_string()));
+        let err = lines.next().unwrap().unwrap_err();
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(7));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(6)));
+    }
+
+    #[test]
+    fn refuses_ambiguous_encodings() {
+        for encoding in [UTF_16LE, ISO_2022_JP] {
+            let err = b""
+                .as_slice()
+                .read_back_encoded_lines(encoding)
+                .unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        }
+    }
+}
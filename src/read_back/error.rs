@@ -0,0 +1,246 @@
+use std::{fmt, io, str::Utf8Error};
+
+/// The operation which failed with a [`ReadBackError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadBackOperation {
+    /// [`ReadBack::read_back_to_string`](crate::ReadBack::read_back_to_string)
+    ReadBackToString,
+    /// [`BufReadBack::read_back_line`](crate::BufReadBack::read_back_line) and [`ReadBackLines`](crate::ReadBackLines)
+    ReadBackLine,
+    /// `BufReadBack::read_back_graphemes` (`graphemes` feature)
+    ReadBackGraphemes,
+    /// [`BufReadBack::read_back_utf16_lines`](crate::BufReadBack::read_back_utf16_lines)
+    ReadBackUtf16Lines,
+    /// `BufReadBack::read_back_encoded_lines` (`encoding` feature)
+    ReadBackEncodedLines,
+}
+
+impl fmt::Display for ReadBackOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadBackToString => write!(f, "read_back_to_string"),
+            Self::ReadBackLine => write!(f, "read_back_line"),
+            Self::ReadBackGraphemes => write!(f, "read_back_graphemes"),
+            Self::ReadBackUtf16Lines => write!(f, "read_back_utf16_lines"),
+            Self::ReadBackEncodedLines => write!(f, "read_back_encoded_lines"),
+        }
+    }
+}
+
+/// The error if the read-back bytes aren't valid text, like invalid UTF-8.
+///
+/// It's returned wrapped in an [`io::Error`] of kind [`io::ErrorKind::InvalidData`], so you can get it back with
+/// [`io::Error::get_ref`] and [`downcast_ref`].
+///
+/// # Example
+/// ```
+/// use read_collection::{ReadBack, ReadBackError, ReadBackOperation};
+///
+/// fn main() {
+///     let data = b"valid \xFF valid";
+///     let mut buffer = String::new();
+///
+///     let err = data.as_slice().read_back_to_string(&mut buffer).unwrap_err();
+///     let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>().unwrap();
+///
+///     assert_eq!(err.operation(), ReadBackOperation::ReadBackToString);
+///     assert_eq!(err.offset_from_start(), Some(6));
+///     assert_eq!(err.offset_from_end(), 7);
+/// }
+/// ```
+///
+/// [`downcast_ref`]: https://doc.rust-lang.org/std/error/trait.Error.html#method.downcast_ref-1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBackError {
+    operation: ReadBackOperation,
+    offset_from_end: u64,
+    offset_from_start: Option<u64>,
+    encoding: &'static str,
+    source: Option<Utf8Error>,
+    /// The amount of bytes which have been read back by the failed operation, if it's known.
+    amount_read: u64,
+}
+
+impl ReadBackError {
+    /// The first invalid byte is `offset_from_end` bytes in front of the position where reading back started.
+    pub(crate) fn invalid_text(
+        operation: ReadBackOperation,
+        encoding: &'static str,
+        offset_from_end: u64,
+        offset_from_start: Option<u64>,
+    ) -> Self {
+        Self {
+            operation,
+            offset_from_end,
+            offset_from_start,
+            encoding,
+            source: None,
+            amount_read: 0,
+        }
+    }
+
+    /// `bytes` are the read-back bytes which should have been converted to a string and `at_start` tells if they
+    /// begin at the start of the source.
+    pub(crate) fn invalid_utf8(
+        operation: ReadBackOperation,
+        bytes: &[u8],
+        source: Utf8Error,
+        at_start: bool,
+    ) -> Self {
+        let valid_up_to = source.valid_up_to() as u64;
+
+        Self {
+            source: Some(source),
+            amount_read: bytes.len() as u64,
+            ..Self::invalid_text(
+                operation,
+                "UTF-8",
+                bytes.len() as u64 - valid_up_to,
+                at_start.then_some(valid_up_to),
+            )
+        }
+    }
+
+    /// Moves the offset from the end by `amount` bytes, if the reading back started `amount` bytes
+    /// behind the failed operation.
+    pub(crate) fn shift(&mut self, amount: u64) {
+        self.offset_from_end += amount;
+    }
+
+    /// Returns the amount of bytes which have been read back (and consumed) by the failed operation.
+    pub(crate) fn amount_read(&self) -> u64 {
+        self.amount_read
+    }
+
+    /// Returns the operation which failed.
+    pub fn operation(&self) -> ReadBackOperation {
+        self.operation
+    }
+
+    /// Returns the position of the first invalid byte, counted backwards from the position where
+    /// reading back started.
+    ///
+    /// An offset of `1` means that the last read-back byte is invalid.
+    pub fn offset_from_end(&self) -> u64 {
+        self.offset_from_end
+    }
+
+    /// Returns the position of the first invalid byte, counted from the start of the source.
+    ///
+    /// It's only known if the operation read back until the start of the source.
+    pub fn offset_from_start(&self) -> Option<u64> {
+        self.offset_from_start
+    }
+
+    /// Returns the name of the encoding which the bytes should have, like `UTF-8` or `UTF-16`.
+    pub fn encoding(&self) -> &'static str {
+        self.encoding
+    }
+
+    /// Returns the underlying UTF-8 error, if the bytes should have been UTF-8.
+    ///
+    /// Its [`valid_up_to`] refers to the bytes which were read back by the failing operation.
+    ///
+    /// [`valid_up_to`]: Utf8Error::valid_up_to
+    pub fn utf8_error(&self) -> Option<&Utf8Error> {
+        self.source.as_ref()
+    }
+}
+
+impl fmt::Display for ReadBackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: invalid {} {} bytes before the end",
+            self.operation, self.encoding, self.offset_from_end
+        )?;
+
+        if let Some(offset) = self.offset_from_start {
+            write!(f, " ({} bytes after the start)", offset)?;
+        }
+
+        match &self.source {
+            Some(source) => write!(f, ": {}", source),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ReadBackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<ReadBackError> for io::Error {
+    fn from(err: ReadBackError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf8_error(bytes: &[u8]) -> Utf8Error {
+        std::str::from_utf8(bytes).unwrap_err()
+    }
+
+    #[test]
+    fn offsets() {
+        let bytes = b"ab\xFFcd";
+        let err = ReadBackError::invalid_utf8(
+            ReadBackOperation::ReadBackLine,
+            bytes,
+            utf8_error(bytes),
+            false,
+        );
+
+        assert_eq!(err.offset_from_end(), 3);
+        assert_eq!(err.offset_from_start(), None);
+        assert_eq!(err.encoding(), "UTF-8");
+    }
+
+    #[test]
+    fn other_encodings() {
+        let err = ReadBackError::invalid_text(
+            ReadBackOperation::ReadBackUtf16Lines,
+            "UTF-16",
+            4,
+            Some(2),
+        );
+
+        assert_eq!(err.utf8_error(), None);
+        assert_eq!(
+            err.to_string(),
+            "read_back_utf16_lines: invalid UTF-16 4 bytes before the end (2 bytes after the start)"
+        );
+    }
+
+    #[test]
+    fn into_io_error() {
+        let bytes = b"\xFF";
+        let err = ReadBackError::invalid_utf8(
+            ReadBackOperation::ReadBackToString,
+            bytes,
+            utf8_error(bytes),
+            true,
+        );
+
+        let io_err = io::Error::from(err.clone());
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            io_err.get_ref().unwrap().downcast_ref::<ReadBackError>(),
+            Some(&err)
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "read_back_to_string: invalid UTF-8 1 bytes before the end (0 bytes after the start): {}",
+                utf8_error(bytes)
+            )
+        );
+    }
+}
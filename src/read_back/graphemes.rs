@@ -0,0 +1,271 @@
+use std::io::{self, ErrorKind};
+
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+use super::rev_buf::RevBuf;
+use crate::{BufReadBack, ReadBackError, ReadBackOperation};
+
+/// The amount of bytes in front of the end which are handed to the grapheme cursor at first.
+/// Most clusters are way smaller, bigger ones just need more rounds.
+const WINDOW_SIZE: usize = 32;
+
+/// An iterator over the extended grapheme clusters of an instance of [`BufReadBack`], starting with the last one.
+///
+/// This struct is generally created by calling [`read_back_graphemes`] on a [`BufReadBack`].
+/// Please see the documentation of [`read_back_graphemes`] for more details.
+///
+/// [`read_back_graphemes`]: BufReadBack::read_back_graphemes
+#[derive(Debug)]
+pub struct ReadBackGraphemes<B> {
+    buf: B,
+    /// The read-back bytes which haven't been returned yet.
+    bytes: RevBuf,
+    /// Everything in front of it belongs to a character whose first bytes haven't been read back yet.
+    text_start: usize,
+    at_start: bool,
+    /// The amount of bytes of the returned clusters.
+    returned: u64,
+}
+
+impl<B> ReadBackGraphemes<B> {
+    pub(crate) fn new(buf: B) -> Self {
+        Self {
+            buf,
+            bytes: RevBuf::default(),
+            text_start: 0,
+            at_start: false,
+            returned: 0,
+        }
+    }
+
+    /// Returns the position of `index` (of `self.bytes`) for the grapheme cursor.
+    ///
+    /// Until the start of the source has been reached, the text starts at `1` instead of `0`, so the cursor asks
+    /// for the text in front of it instead of treating it as the start of the string.
+    fn cursor_pos(&self, index: usize) -> usize {
+        index - self.text_start + usize::from(!self.at_start)
+    }
+
+    fn text(&self, start: usize, end: usize) -> io::Result<&str> {
+        std::str::from_utf8(&self.bytes.as_slice()[start..end]).map_err(|err| {
+            let mut err = ReadBackError::invalid_utf8(
+                ReadBackOperation::ReadBackGraphemes,
+                &self.bytes.as_slice()[start..],
+                err,
+                self.at_start && start == 0,
+            );
+            err.shift(self.returned);
+            err.into()
+        })
+    }
+
+    /// Returns the error for the invalid byte at `index` (of `self.bytes`), which doesn't fit into a `Utf8Error`.
+    fn invalid(&self, index: usize) -> io::Error {
+        let offset_from_end = self.returned + (self.bytes.len() - index) as u64;
+        let offset_from_start = self.at_start.then_some(index as u64);
+
+        ReadBackError::invalid_text(
+            ReadBackOperation::ReadBackGraphemes,
+            "UTF-8",
+            offset_from_end,
+            offset_from_start,
+        )
+        .into()
+    }
+}
+
+impl<B: BufReadBack> ReadBackGraphemes<B> {
+    /// Puts the next chunk of the source in front of the collected bytes and validates the characters which are
+    /// complete now.
+    fn refill(&mut self) -> io::Result<()> {
+        let chunk = loop {
+            match self.buf.read_back_fill_buf() {
+                Ok(chunk) => break chunk,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        };
+
+        if chunk.is_empty() {
+            self.at_start = true;
+            // the source starts with continuation bytes
+            return match self.text_start {
+                0 => Ok(()),
+                _ => Err(self.invalid(0)),
+            };
+        }
+
+        let amount = chunk.len();
+        self.bytes.prepend(chunk);
+        self.buf.read_back_consume(amount);
+
+        let unchecked = &self.bytes.as_slice()[..self.text_start + amount];
+        // a UTF-8 character has at most 3 continuation bytes
+        let start = match unchecked.iter().take(4).position(|&b| !is_continuation(b)) {
+            Some(start) => start,
+            None if unchecked.len() < 4 => unchecked.len(),
+            // too many continuation bytes in a row, the last one can't belong to a character
+            None => return Err(self.invalid(3)),
+        };
+
+        self.text(start, unchecked.len())?;
+        self.text_start = start;
+        Ok(())
+    }
+
+    /// Returns the start of the last grapheme cluster in `self.bytes` or `None` if more bytes are needed.
+    fn last_boundary(&self) -> io::Result<Option<usize>> {
+        let len = self.bytes.len();
+        let end = self.cursor_pos(len);
+        let mut cursor = GraphemeCursor::new(end, end, true);
+
+        let mut window_start = len.saturating_sub(WINDOW_SIZE).max(self.text_start);
+        loop {
+            while is_continuation(self.bytes.as_slice()[window_start]) {
+                window_start -= 1;
+            }
+
+            let window = self.text(window_start, len)?;
+            match cursor.prev_boundary(window, self.cursor_pos(window_start)) {
+                Ok(Some(boundary)) => {
+                    return Ok(Some(
+                        boundary + self.text_start - self.cursor_pos(self.text_start),
+                    ))
+                }
+                Ok(None) => return Ok(Some(self.text_start)),
+                Err(GraphemeIncomplete::PrevChunk) if window_start > self.text_start => {
+                    let size = len - window_start;
+                    window_start = len.saturating_sub(size * 2).max(self.text_start);
+                }
+                Err(GraphemeIncomplete::PreContext(context_end)) => {
+                    let context_end =
+                        context_end + self.text_start - self.cursor_pos(self.text_start);
+                    if context_end == self.text_start {
+                        return Ok(None);
+                    }
+
+                    let context = self.text(self.text_start, context_end)?;
+                    cursor.provide_context(context, self.cursor_pos(self.text_start));
+                }
+                Err(GraphemeIncomplete::PrevChunk) => return Ok(None),
+                Err(err) => unreachable!("grapheme cursor failed while going backwards: {:?}", err),
+            }
+        }
+    }
+}
+
+impl<B: BufReadBack> Iterator for ReadBackGraphemes<B> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        loop {
+            if self.bytes.len() > self.text_start {
+                match self.last_boundary() {
+                    Ok(Some(start)) => {
+                        let grapheme = self.text(start, self.bytes.len()).map(String::from);
+                        self.returned += (self.bytes.len() - start) as u64;
+                        self.bytes.truncate(start);
+                        return Some(grapheme);
+                    }
+                    Ok(None) => (),
+                    Err(err) => return Some(Err(err)),
+                }
+            } else if self.at_start {
+                return None;
+            }
+
+            if let Err(err) = self.refill() {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufReadBacker;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    #[test]
+    fn same_as_graphemes() {
+        let texts = [
+            "",
+            "a",
+            "plain ascii",
+            "e\u{301}\u{302} combining marks",
+            "crlf\r\n\r\n",
+            "family: 👩‍👩‍👧‍👦, flags: 🇩🇪🇫🇷🇮🇹",
+            "🇩🇪🇫🇷🇮🇹🇯",
+            "👍🏽 zwj sequence 🏳️‍🌈",
+            "한국어 각",
+        ];
+
+        for text in texts {
+            let expected: Vec<&str> = text.graphemes(true).rev().collect();
+
+            for capacity in [1, 2, 3, 5, 64] {
+                let graphemes = BufReadBacker::with_capacity(capacity, text.as_bytes())
+                    .read_back_graphemes()
+                    .collect::<io::Result<Vec<String>>>()
+                    .unwrap();
+
+                assert_eq!(
+                    graphemes, expected,
+                    "text: {:?}, capacity: {}",
+                    text, capacity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn long_cluster() {
+        let text = format!("a{}b", "\u{301}".repeat(1000));
+
+        let graphemes = text
+            .as_bytes()
+            .read_back_graphemes()
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap();
+
+        assert_eq!(graphemes, ["b", &text[..text.len() - 1]]);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let data = b"\xFFab";
+        let mut graphemes = BufReadBacker::with_capacity(1, data.as_slice()).read_back_graphemes();
+
+        assert_eq!(graphemes.next().unwrap().ok(), Some("b".to_string()));
+        // the character in front of "a" is needed to know where its cluster starts
+        let err = graphemes.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(
+            err.map(|e| e.operation()),
+            Some(ReadBackOperation::ReadBackGraphemes)
+        );
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(3));
+        // the start hasn't been reached yet
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(None));
+    }
+
+    #[test]
+    fn truncated_character_at_start() {
+        let data = "é".as_bytes();
+        let mut graphemes = data[1..].read_back_graphemes();
+
+        let err = graphemes.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(1));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(0)));
+    }
+}
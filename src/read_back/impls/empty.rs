@@ -6,6 +6,10 @@ impl ReadBack for Empty {
     fn read_back(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
         Ok(0)
     }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(0))
+    }
 }
 
 impl BufReadBack for Empty {
@@ -1,32 +1,584 @@
 use std::{
+    cmp,
     fs::File,
-    io::{Read, Seek, SeekFrom},
+    io::{self, ErrorKind, IoSliceMut, Seek, SeekFrom},
 };
 
 use crate::ReadBack;
 
-impl ReadBack for &File {
-    fn read_back(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let curr_pos = self.stream_position()?;
+#[cfg(unix)]
+mod uninit {
+    use std::{
+        fs::File,
+        io::{self, ErrorKind},
+        os::unix::io::AsRawFd,
+    };
+
+    use crate::BorrowedBackBuf;
+
+    /// Converts a position in a file to an `off_t`.
+    pub fn offset(pos: u64) -> io::Result<libc::off_t> {
+        libc::off_t::try_from(pos)
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "file offset is too big"))
+    }
+
+    /// Reads the bytes in front of the position directly into the unfilled part of `buf` with `pread`.
+    pub fn read_back_buf(file: &File, buf: &mut BorrowedBackBuf<'_>) -> io::Result<()> {
+        if buf.remaining() == 0 {
+            return Ok(());
+        }
+        let (start, amount) = super::seek_back(file, buf.remaining())?;
+
+        // SAFETY: `pread` only writes initialised bytes.
+        let unfilled = unsafe { buf.unfilled_mut() };
+        let dest_start = unfilled.len() - amount;
+        let dest = &mut unfilled[dest_start..];
+
+        let mut read = 0;
+        while read < amount {
+            let offset = offset(start + read as u64)
+                .map_err(|err| super::undo_seek_back(file, start, amount, err))?;
+
+            // SAFETY: The pointer and the length are within `dest`.
+            let n = unsafe {
+                libc::pread(
+                    file.as_raw_fd(),
+                    dest[read..].as_mut_ptr().cast(),
+                    amount - read,
+                    offset,
+                )
+            };
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(super::undo_seek_back(file, start, amount, err));
+            } else if n == 0 {
+                return Err(super::undo_seek_back(
+                    file,
+                    start,
+                    amount,
+                    super::truncated(),
+                ));
+            }
+            read += n as usize;
+        }
+
+        // SAFETY: `pread` has initialised the last `amount` unfilled bytes.
+        unsafe { buf.advance_unchecked(amount) };
+        Ok(())
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod vectored {
+    use std::{
+        cmp,
+        fs::File,
+        io::{self, ErrorKind, IoSliceMut},
+        os::unix::io::AsRawFd,
+    };
+
+    /// The smallest `IOV_MAX` of the supported systems.
+    const MAX_IOVECS: usize = 1024;
+
+    pub const IS_VECTORED: bool = true;
+
+    /// Reads the bytes in front of the position with a single `preadv` call (unless it returns less).
+    pub fn read_back_vectored(file: &File, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let len = bufs
+            .iter()
+            .rev()
+            .take(MAX_IOVECS)
+            .fold(0usize, |len, buf| len.saturating_add(buf.len()));
+        if len == 0 {
+            return Ok(0);
+        }
+        let (start, amount) = super::seek_back(file, len)?;
+
+        // the last buffer gets the last bytes and only the first used buffer may be filled partially
+        let mut iovecs = Vec::new();
+        let mut filled = 0;
+        for buf in bufs.iter_mut().rev().take(MAX_IOVECS) {
+            if filled == amount {
+                break;
+            }
+
+            let len = cmp::min(buf.len(), amount - filled);
+            if len > 0 {
+                iovecs.push(libc::iovec {
+                    iov_base: buf.as_mut_ptr().cast(),
+                    iov_len: len,
+                });
+                filled += len;
+            }
+        }
+        iovecs.reverse();
+
+        let mut remaining = iovecs.as_mut_slice();
+        let mut read: u64 = 0;
+        while !remaining.is_empty() {
+            let offset = super::uninit::offset(start + read)
+                .map_err(|err| super::undo_seek_back(file, start, amount, err))?;
+
+            // SAFETY: The iovecs point into `bufs`, which are borrowed mutably for the whole function, and their
+            // lengths are within the buffers.
+            let n = unsafe {
+                libc::preadv(
+                    file.as_raw_fd(),
+                    remaining.as_ptr(),
+                    remaining.len() as libc::c_int,
+                    offset,
+                )
+            };
+            if n < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(super::undo_seek_back(file, start, amount, err));
+            } else if n == 0 {
+                return Err(super::undo_seek_back(
+                    file,
+                    start,
+                    amount,
+                    super::truncated(),
+                ));
+            }
+            read += n as u64;
+
+            // skip the filled parts
+            let mut n = n as usize;
+            while let Some(first) = remaining.first_mut() {
+                if n < first.iov_len {
+                    // SAFETY: `n` is within the buffer of the iovec.
+                    first.iov_base = unsafe { first.iov_base.cast::<u8>().add(n).cast() };
+                    first.iov_len -= n;
+                    break;
+                }
+                n -= first.iov_len;
+                remaining = &mut std::mem::take(&mut remaining)[1..];
+            }
+        }
+
+        Ok(amount)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+mod vectored {
+    use std::{
+        fs::File,
+        io::{self, IoSliceMut},
+    };
+
+    use crate::{read_back::default_read_back_vectored, ReadBack};
+
+    pub const IS_VECTORED: bool = false;
 
-        let buf_len = buf.len() as u64;
-        let max_amount_read = std::cmp::min(curr_pos, buf_len);
+    pub fn read_back_vectored(mut file: &File, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        default_read_back_vectored(|b| file.read_back(b), bufs)
+    }
+}
+
+/// Asks the kernel to read the `amount` bytes in front of the position into the page cache.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn advise(mut file: &File, amount: usize) {
+    use std::os::unix::io::AsRawFd;
+
+    let pos = match file.stream_position() {
+        Ok(pos) => pos,
+        Err(_) => return,
+    };
+    let amount = cmp::min(amount as u64, pos);
+    // a length of zero would mean "until the end of the file"
+    if amount == 0 {
+        return;
+    }
 
-        self.seek(SeekFrom::Current(-(max_amount_read as i64)))?;
-        let (_left, right) = buf.split_at_mut((buf_len - max_amount_read) as usize);
-        match self.read(right) {
+    if let (Ok(offset), Ok(len)) = (uninit::offset(pos - amount), uninit::offset(amount)) {
+        // SAFETY: `posix_fadvise` doesn't access any memory of this process.
+        unsafe { libc::posix_fadvise(file.as_raw_fd(), offset, len, libc::POSIX_FADV_WILLNEED) };
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn advise(_file: &File, _amount: usize) {}
+
+pub(crate) fn truncated() -> io::Error {
+    io::Error::new(
+        ErrorKind::UnexpectedEof,
+        "the file has been truncated while reading it back",
+    )
+}
+
+/// The system calls which are needed to read back a file without moving its position back and forth.
+pub(crate) trait PositionalRead {
+    /// Reads from `offset` on. The position of the file may change.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Moves the position of the file like [`Seek::seek`] and returns the new one.
+    fn move_position(&self, pos: SeekFrom) -> io::Result<u64>;
+}
+
+impl PositionalRead for File {
+    #[cfg(unix)]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut file = self;
+        file.seek(SeekFrom::Start(offset))?;
+        io::Read::read(&mut file, buf)
+    }
+
+    fn move_position(&self, pos: SeekFrom) -> io::Result<u64> {
+        let mut file = self;
+        file.seek(pos)
+    }
+}
+
+/// Moves the position up to `len` bytes back, so the position of the file is only updated once per read.
+///
+/// Returns the new position and the amount of bytes between it and the old one. A single relative seek does both,
+/// except if the position is less than `len` bytes after the start.
+fn seek_back<F: PositionalRead + ?Sized>(file: &F, len: usize) -> io::Result<(u64, usize)> {
+    let len = i64::try_from(len).unwrap_or(i64::MAX);
+    match file.move_position(SeekFrom::Current(-len)) {
+        Ok(start) => Ok((start, len as usize)),
+        // the seek would end in front of the start
+        Err(err) if err.kind() == ErrorKind::InvalidInput => {
+            let pos = file.move_position(SeekFrom::Current(0))?;
+            file.move_position(SeekFrom::Start(0))?;
+            Ok((0, pos as usize))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Moves the position back to where it was before [`seek_back`], because reading has failed.
+fn undo_seek_back<F: PositionalRead + ?Sized>(
+    file: &F,
+    start: u64,
+    amount: usize,
+    err: io::Error,
+) -> io::Error {
+    let _ = file.move_position(SeekFrom::Start(start + amount as u64));
+    err
+}
+
+/// Fills `buf` with the bytes from `offset` on, even if the reads return less than requested.
+fn read_exact_at<F: PositionalRead + ?Sized>(
+    file: &F,
+    mut buf: &mut [u8],
+    mut offset: u64,
+) -> io::Result<()> {
+    while !buf.is_empty() {
+        match file.read_at(buf, offset) {
+            Ok(0) => return Err(truncated()),
             Ok(n) => {
-                let offset = std::cmp::min(max_amount_read, n as u64) as i64;
-                self.seek(std::io::SeekFrom::Current(-offset))?;
-                Ok(n)
+                buf = &mut buf[n..];
+                offset += n as u64;
             }
-            Err(err) => Err(err),
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+/// Reads back with one relative seek and one positional read (unless it returns less).
+fn read_back_at<F: PositionalRead + ?Sized>(file: &F, buf: &mut [u8]) -> io::Result<usize> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+
+    let (start, amount) = seek_back(file, buf.len())?;
+    read_exact_at(file, &mut buf[..amount], start)
+        .map_err(|err| undo_seek_back(file, start, amount, err))?;
+    Ok(amount)
+}
+
+/// Reads back everything in front of the position and updates the position only once.
+fn read_back_to_end_at<F: PositionalRead + ?Sized>(
+    file: &F,
+    buf: &mut Vec<u8>,
+) -> io::Result<usize> {
+    let pos = file.move_position(SeekFrom::Current(0))?;
+    let amount = usize::try_from(pos).map_err(|_| ErrorKind::OutOfMemory)?;
+
+    let mut data = Vec::new();
+    data.try_reserve(amount.saturating_add(buf.len()))
+        .map_err(|_| ErrorKind::OutOfMemory)?;
+    data.resize(amount, 0);
+
+    read_exact_at(file, &mut data, 0)?;
+    file.move_position(SeekFrom::Start(0))?;
+
+    data.extend_from_slice(buf);
+    *buf = data;
+    Ok(amount)
+}
+
+impl ReadBack for &File {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        read_back_at(*self, buf)
+    }
+
+    fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        read_back_to_end_at(*self, buf)
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+        vectored::read_back_vectored(self, bufs)
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        vectored::IS_VECTORED
+    }
+
+    #[cfg(unix)]
+    fn read_back_buf(&mut self, buf: &mut crate::BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        uninit::read_back_buf(self, buf)
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        // everything in front of the position can be read back
+        let mut file: &File = self;
+        match file.stream_position().map(usize::try_from) {
+            Ok(Ok(pos)) => (pos, Some(pos)),
+            _ => (0, None),
         }
     }
+
+    fn read_back_advise(&self, amount: usize) {
+        advise(self, amount)
+    }
 }
 
 impl ReadBack for File {
     fn read_back(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         (&*self).read_back(buf)
     }
+
+    fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        (&*self).read_back_to_end(buf)
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
+        (&*self).read_back_vectored(bufs)
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        vectored::IS_VECTORED
+    }
+
+    fn read_back_buf(&mut self, buf: &mut crate::BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        (&*self).read_back_buf(buf)
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        <&File as ReadBack>::read_back_size_hint(&self)
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        advise(self, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+    use std::{cell::Cell, io::Write};
+
+    /// A file in memory which counts the system calls and returns at most `max_read` bytes per read.
+    struct CountingFile {
+        data: Vec<u8>,
+        pos: Cell<u64>,
+        max_read: usize,
+        calls: Cell<usize>,
+    }
+
+    impl CountingFile {
+        fn new(data: &[u8], max_read: usize) -> Self {
+            Self {
+                data: data.to_vec(),
+                pos: Cell::new(data.len() as u64),
+                max_read,
+                calls: Cell::new(0),
+            }
+        }
+
+        fn count(&self) {
+            self.calls.set(self.calls.get() + 1);
+        }
+
+        /// Reads from the position on and moves it, like [`io::Read::read`].
+        fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.read_at(buf, self.pos.get())?;
+            self.pos.set(self.pos.get() + n as u64);
+            Ok(n)
+        }
+    }
+
+    impl PositionalRead for CountingFile {
+        fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            self.count();
+            let mut rest = self.data.get(offset as usize..).unwrap_or_default();
+            rest = &rest[..cmp::min(rest.len(), self.max_read)];
+            io::Read::read(&mut rest, buf)
+        }
+
+        fn move_position(&self, pos: SeekFrom) -> io::Result<u64> {
+            self.count();
+            let pos = match pos {
+                SeekFrom::Start(pos) => Some(pos),
+                SeekFrom::End(offset) => (self.data.len() as u64).checked_add_signed(offset),
+                SeekFrom::Current(offset) => self.pos.get().checked_add_signed(offset),
+            };
+            let pos = pos.ok_or(ErrorKind::InvalidInput)?;
+            self.pos.set(pos);
+            Ok(pos)
+        }
+    }
+
+    /// Reads back like before the positional reads: getting the position, seeking back, reading and seeking back
+    /// again.
+    fn seek_around_read(file: &CountingFile, buf: &mut [u8]) -> io::Result<usize> {
+        let pos = file.move_position(SeekFrom::Current(0))?;
+        let amount = cmp::min(pos, buf.len() as u64);
+
+        file.move_position(SeekFrom::Current(-(amount as i64)))?;
+        let n = file.read(&mut buf[..amount as usize])?;
+        file.move_position(SeekFrom::Current(-(n as i64)))?;
+        Ok(n)
+    }
+
+    #[test]
+    fn one_seek_and_one_positional_read_per_chunk() {
+        let before = CountingFile::new(b"0123456789", usize::MAX);
+        let after = CountingFile::new(b"0123456789", usize::MAX);
+
+        for (expected, pos) in [(b"6789", 6), (b"2345", 2)] {
+            let mut buf = [0; 4];
+            assert_eq!(seek_around_read(&before, &mut buf).ok(), Some(4));
+            assert_eq!(&buf, expected);
+            assert_eq!(before.pos.get(), pos);
+
+            let mut buf = [0; 4];
+            assert_eq!(read_back_at(&after, &mut buf).ok(), Some(4));
+            assert_eq!(&buf, expected);
+            assert_eq!(after.pos.get(), pos);
+        }
+        // 4 system calls per chunk before, 2 after
+        assert_eq!(before.calls.get(), 8);
+        assert_eq!(after.calls.get(), 4);
+
+        let mut buf = Vec::new();
+        assert_eq!(read_back_to_end_at(&after, &mut buf).ok(), Some(2));
+        assert_eq!(buf, b"01");
+        assert_eq!(after.pos.get(), 0);
+        assert_eq!(after.calls.get(), 7);
+    }
+
+    #[test]
+    fn close_to_the_start() {
+        let file = CountingFile::new(b"0123456789", usize::MAX);
+        file.pos.set(2);
+        let mut buf = [0; 4];
+
+        assert_eq!(read_back_at(&file, &mut buf).ok(), Some(2));
+        assert_eq!(&buf[..2], b"01");
+        assert_eq!(file.pos.get(), 0);
+        // the failed relative seek, getting the position, seeking to the start and reading
+        assert_eq!(file.calls.get(), 4);
+    }
+
+    #[test]
+    fn short_reads_in_the_middle() {
+        let file = CountingFile::new(b"0123456789", 3);
+        file.pos.set(9);
+        let mut buf = [0; 7];
+
+        assert_eq!(read_back_at(&file, &mut buf).ok(), Some(7));
+        assert_eq!(&buf, b"2345678");
+        assert_eq!(file.pos.get(), 2);
+        assert_eq!(file.calls.get(), 4);
+    }
+
+    #[test]
+    fn truncated_file() {
+        let file = CountingFile::new(b"0123", usize::MAX);
+        file.pos.set(10);
+
+        let err = read_back_at(&file, &mut [0; 8]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        // nothing has been read back
+        assert_eq!(file.pos.get(), 10);
+    }
+
+    fn file(content: &[u8]) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content).unwrap();
+        file
+    }
+
+    #[test]
+    fn conformance() {
+        testing::check_read_back_with_config(
+            proptest::test_runner::Config::with_cases(64),
+            |content| Box::new(file(content)),
+        );
+    }
+
+    #[test]
+    fn vectored_fills_the_last_buffer_first() {
+        let mut file = file(b"0123456789");
+        file.seek(SeekFrom::Start(8)).unwrap();
+
+        let mut first = [b'_'; 4];
+        let mut empty = [0; 0];
+        let mut second = [b'_'; 3];
+        let amount = file
+            .read_back_vectored(&mut [
+                IoSliceMut::new(&mut first),
+                IoSliceMut::new(&mut empty),
+                IoSliceMut::new(&mut second),
+            ])
+            .unwrap();
+
+        assert_eq!(amount, 7);
+        assert_eq!(&first, b"1234");
+        assert_eq!(&second, b"567");
+        assert_eq!(file.stream_position().unwrap(), 1);
+
+        let amount = file
+            .read_back_vectored(&mut [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)])
+            .unwrap();
+        assert_eq!(amount, 1);
+        assert_eq!(&second, b"067");
+    }
 }
@@ -4,7 +4,9 @@ mod empty;
 mod file;
 mod u8_slice;
 
-impl<R: ReadBack> ReadBack for &mut R {
+pub(crate) use file::{truncated, PositionalRead};
+
+impl<R: ReadBack + ?Sized> ReadBack for &mut R {
     fn read_back(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         (**self).read_back(buf)
     }
@@ -16,6 +18,14 @@ impl<R: ReadBack> ReadBack for &mut R {
         (**self).read_back_vectored(bufs)
     }
 
+    fn is_read_back_vectored(&self) -> bool {
+        (**self).is_read_back_vectored()
+    }
+
+    fn read_back_buf(&mut self, buf: &mut crate::BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        (**self).read_back_buf(buf)
+    }
+
     fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
         (**self).read_back_to_end(buf)
     }
@@ -27,9 +37,17 @@ impl<R: ReadBack> ReadBack for &mut R {
     fn read_back_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
         (**self).read_back_exact(buf)
     }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        (**self).read_back_size_hint()
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        (**self).read_back_advise(amount)
+    }
 }
 
-impl<R: ReadBack> ReadBack for Box<R> {
+impl<R: ReadBack + ?Sized> ReadBack for Box<R> {
     fn read_back(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         (**self).read_back(buf)
     }
@@ -41,6 +59,14 @@ impl<R: ReadBack> ReadBack for Box<R> {
         (**self).read_back_vectored(bufs)
     }
 
+    fn is_read_back_vectored(&self) -> bool {
+        (**self).is_read_back_vectored()
+    }
+
+    fn read_back_buf(&mut self, buf: &mut crate::BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        (**self).read_back_buf(buf)
+    }
+
     fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
         (**self).read_back_to_end(buf)
     }
@@ -52,9 +78,17 @@ impl<R: ReadBack> ReadBack for Box<R> {
     fn read_back_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
         (**self).read_back_exact(buf)
     }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        (**self).read_back_size_hint()
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        (**self).read_back_advise(amount)
+    }
 }
 
-impl<R: BufReadBack> BufReadBack for &mut R {
+impl<R: BufReadBack + ?Sized> BufReadBack for &mut R {
     fn read_back_fill_buf(&mut self) -> std::io::Result<&[u8]> {
         (**self).read_back_fill_buf()
     }
@@ -80,7 +114,7 @@ impl<R: BufReadBack> BufReadBack for &mut R {
     }
 }
 
-impl<R: BufReadBack> BufReadBack for Box<R> {
+impl<R: BufReadBack + ?Sized> BufReadBack for Box<R> {
     fn read_back_fill_buf(&mut self) -> std::io::Result<&[u8]> {
         (**self).read_back_fill_buf()
     }
@@ -1,8 +1,10 @@
 use std::cmp;
 use std::io::IoSliceMut;
 
+use crate::BorrowedBackBuf;
 use crate::BufReadBack;
 use crate::ReadBack;
+use crate::{ReadBackError, ReadBackOperation};
 
 /// As for the [`Read`] implementation of `&[u8]`, bytes get copied from the slice.
 ///
@@ -27,7 +29,8 @@ impl ReadBack for &[u8] {
 
     fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> std::io::Result<usize> {
         let mut amount_read = 0;
-        for buf in bufs {
+        // the last buffer gets the last bytes
+        for buf in bufs.iter_mut().rev() {
             amount_read += self.read_back(buf)?;
             if self.is_empty() {
                 break;
@@ -37,6 +40,23 @@ impl ReadBack for &[u8] {
         Ok(amount_read)
     }
 
+    fn is_read_back_vectored(&self) -> bool {
+        true
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> std::io::Result<()> {
+        let amount = cmp::min(buf.remaining(), self.len());
+        let (left, right) = self.split_at(self.len() - amount);
+        buf.prepend(right);
+
+        *self = left;
+        Ok(())
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+
     fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
         let len = self.len();
         buf.try_reserve(len)
@@ -52,13 +72,21 @@ impl ReadBack for &[u8] {
     }
 
     fn read_back_to_string(&mut self, buf: &mut String) -> std::io::Result<usize> {
-        let mut self_string = String::from_utf8(self.to_vec())
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-
-        self_string.push_str(&buf);
+        let mut self_string = String::from_utf8(self.to_vec()).map_err(|e| {
+            ReadBackError::invalid_utf8(
+                ReadBackOperation::ReadBackToString,
+                self,
+                e.utf8_error(),
+                true,
+            )
+        })?;
+
+        self_string.push_str(buf);
         *buf = self_string;
 
-        Ok(self.len())
+        let len = self.len();
+        *self = &[];
+        Ok(len)
     }
 
     fn read_back_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
@@ -117,6 +145,7 @@ mod tests {
     mod read_back {
         use super::*;
 
+        #[allow(clippy::module_inception)]
         mod read_back {
             use super::*;
 
@@ -212,6 +241,23 @@ mod tests {
                 );
                 assert_eq!(&buffer, "I use Arch btw.");
             }
+
+            #[test]
+            fn invalid_utf8() {
+                let data = b"I \xFFuse ";
+                let mut buffer = "Arch btw.".to_string();
+
+                let err = data
+                    .as_slice()
+                    .read_back_to_string(&mut buffer)
+                    .unwrap_err();
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+                let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+                assert_eq!(err.map(|e| e.offset_from_end()), Some(5));
+                assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(2)));
+                assert_eq!(&buffer, "Arch btw.");
+            }
         }
 
         mod read_back_exact {
@@ -403,6 +449,20 @@ mod tests {
                 assert_eq!(data.as_slice().read_back_line(&mut buffer).ok(), Some(4));
                 assert_eq!(buffer.as_bytes(), data);
             }
+
+            #[test]
+            fn invalid_utf8() {
+                let data = b"first\nse\xFFcond";
+                let mut reference: &[u8] = data;
+                let mut buffer = String::new();
+
+                let err = reference.read_back_line(&mut buffer).unwrap_err();
+                let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+
+                assert_eq!(err.map(|e| e.offset_from_end()), Some(5));
+                assert_eq!(err.map(|e| e.offset_from_start()), Some(None));
+                assert!(buffer.is_empty());
+            }
         }
 
         mod read_back_split {
@@ -496,6 +556,44 @@ mod tests {
 
                 assert!(lines.next().is_none());
             }
+
+            #[test]
+            fn invalid_utf8_offsets() {
+                let data = b"fi\xFFrst\nsecond";
+                let mut lines = data.as_slice().read_back_lines();
+
+                assert_eq!(lines.next().unwrap().ok(), Some("second".to_string()));
+
+                let err = lines.next().unwrap().unwrap_err();
+                let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+                assert_eq!(
+                    err.map(|e| e.operation()),
+                    Some(ReadBackOperation::ReadBackLine)
+                );
+                assert_eq!(err.map(|e| e.offset_from_end()), Some(11));
+                assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(2)));
+            }
+
+            #[test]
+            fn consecutive_invalid_lines() {
+                let data = b"a\xFFb\nc\xFEd\ne";
+                let mut lines = data.as_slice().read_back_lines();
+
+                assert_eq!(lines.next().unwrap().ok(), Some("e".to_string()));
+
+                let err = lines.next().unwrap().unwrap_err();
+                let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+                assert_eq!(err.map(|e| e.offset_from_end()), Some(4));
+                assert_eq!(err.map(|e| e.offset_from_start()), Some(None));
+
+                // the offsets of the next line don't include the bytes of the invalid one
+                let err = lines.next().unwrap().unwrap_err();
+                let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+                assert_eq!(err.map(|e| e.offset_from_end()), Some(8));
+                assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(1)));
+
+                assert!(lines.next().is_none());
+            }
         }
 
         mod read_back_take {
@@ -609,6 +707,7 @@ mod tests {
             }
 
             #[test]
+            #[allow(clippy::reversed_empty_ranges)]
             fn general() {
                 let data: [u8; 3] = [1, 2, 3];
 
@@ -1,7 +1,46 @@
+mod borrowed_back_buf;
 mod buf_read_backer;
+mod direct_read_backer;
+#[cfg(feature = "encoding")]
+mod encoded_lines;
+mod error;
+#[cfg(feature = "graphemes")]
+mod graphemes;
 mod impls;
-
+mod prefetching_read_backer;
+mod read_back_concat;
+mod read_back_grep;
+mod read_back_records;
+mod read_back_stats;
+mod rev_buf;
+mod rfind;
+mod rotated_log_read_back;
+mod seek_to_time;
+#[cfg(feature = "spool")]
+mod spooled_read_back;
+mod utf16;
+
+pub use borrowed_back_buf::BorrowedBackBuf;
 pub use buf_read_backer::BufReadBacker;
+pub use direct_read_backer::DirectReadBacker;
+#[cfg(feature = "encoding")]
+pub use encoded_lines::ReadBackEncodedLines;
+pub use error::{ReadBackError, ReadBackOperation};
+#[cfg(feature = "graphemes")]
+pub use graphemes::ReadBackGraphemes;
+pub use prefetching_read_backer::{PrefetchingReadBacker, DEFAULT_PREFETCH_SIZE};
+pub use read_back_concat::ReadBackConcat;
+pub use read_back_grep::{GrepGroup, GrepLine, ReadBackGrep};
+pub use read_back_records::{ReadBackRecords, Record, RecordBounds, DEFAULT_MAX_RECORD_LEN};
+pub use read_back_stats::{
+    ReadBackCounters, ReadBackProgress, ReadBackStats, DEFAULT_PROGRESS_INTERVAL,
+};
+pub use rfind::ReadBackMatches;
+pub use rotated_log_read_back::RotatedLogReadBack;
+pub use seek_to_time::seek_to_time;
+#[cfg(feature = "spool")]
+pub use spooled_read_back::{SpooledReadBack, DEFAULT_SPOOL_THRESHOLD};
+pub use utf16::{ReadBackUtf16Lines, Utf16Endianness};
 
 use std::{
     cmp,
@@ -11,6 +50,8 @@ use std::{
 
 use crate::DEFAULT_BUF_SIZE;
 
+use self::rev_buf::RevBuf;
+
 /// A trait to read back the content which has been read with the methods of [std::io::Read].
 ///
 /// # Example
@@ -67,11 +108,88 @@ pub trait ReadBack {
 
     /// Like [`Read::read_vectored`] but it uses `read_back` instead of `read`.
     ///
+    /// The buffers are filled starting with the last one: The last buffer gets the last bytes in front of the
+    /// current position, the buffer in front of it gets the bytes in front of those and so on. If a buffer is
+    /// only filled partially, its bytes are at its start, like in [`read_back`]. So the filled parts of the
+    /// buffers, in their order, are the bytes which have been read back.
+    ///
+    /// The default implementation calls [`read_back`] with the last non-empty buffer.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::ReadBack;
+    /// use std::io::IoSliceMut;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut data = [1, 2, 3, 4, 5, 6].as_slice();
+    ///     let mut first = [0; 4];
+    ///     let mut second = [0; 3];
+    ///
+    ///     let amount = data.read_back_vectored(&mut [
+    ///         IoSliceMut::new(&mut first),
+    ///         IoSliceMut::new(&mut second),
+    ///     ])?;
+    ///
+    ///     assert_eq!(amount, 6);
+    ///     assert_eq!(first, [1, 2, 3, 0]);
+    ///     assert_eq!(second, [4, 5, 6]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
     /// [`Read::read_vectored`]: std::io::Read::read_vectored
+    /// [`read_back`]: ReadBack::read_back
     fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize> {
         default_read_back_vectored(|b| self.read_back(b), bufs)
     }
 
+    /// Determines if this reader has an efficient [`read_back_vectored`] implementation, like
+    /// [`Read::is_read_vectored`].
+    ///
+    /// If a reader does not override the default [`read_back_vectored`] implementation, code using it may want to
+    /// avoid the method all together and coalesce writes into a single buffer for higher performance.
+    ///
+    /// The default implementation returns `false`.
+    ///
+    /// [`read_back_vectored`]: ReadBack::read_back_vectored
+    /// [`Read::is_read_vectored`]: std::io::Read::is_read_vectored
+    fn is_read_back_vectored(&self) -> bool {
+        false
+    }
+
+    /// Pull some bytes from this source into the specified buffer, which may be uninitialised.
+    ///
+    /// The bytes are put in front of the already filled bytes of `buf`, so the filled bytes stay the bytes in front
+    /// of the current position of this reader. Reading back nothing means that the start of the source has been
+    /// reached, unless `buf` has no remaining space. Like [`Read::read_buf`], this lets buffers skip the
+    /// initialisation of their memory.
+    ///
+    /// The default implementation initialises the remaining space of `buf` and calls [`read_back`] with it.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BorrowedBackBuf, ReadBack};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut data = [1, 2, 3].as_slice();
+    ///     let mut memory = [0; 2];
+    ///     let mut buf = BorrowedBackBuf::from(memory.as_mut_slice());
+    ///
+    ///     data.read_back_buf(&mut buf)?;
+    ///     assert_eq!(buf.filled(), [2, 3]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Same as [`read_back`]. The filled bytes of `buf` stay valid on errors.
+    ///
+    /// [`Read::read_buf`]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_buf
+    /// [`read_back`]: ReadBack::read_back
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> Result<()> {
+        default_read_back_buf(|b| self.read_back(b), buf)
+    }
+
     /// Read all bytes until the start of the source, placing them into `buf`.
     ///
     /// Can be also seen as "read back until you reach the start of the source".
@@ -144,7 +262,8 @@ pub trait ReadBack {
     /// Transforms this `ReadBack` instance to an `Iterator` over its bytes.
     /// This can be also seen as "read the bytes of the instance in reverse".
     ///
-    /// The same conditions of [`Read::bytes`] apply here as well.
+    /// The same conditions of [`Read::bytes`] apply here as well. Every byte is read back with its own
+    /// [`read_back`] call, so [`read_back_bytes_buffered`] is usually faster for unbuffered readers.
     ///
     /// # Example
     /// ```
@@ -163,6 +282,8 @@ pub trait ReadBack {
     /// ```
     ///
     /// [`Read::bytes`]: std::io::Read::bytes
+    /// [`read_back`]: ReadBack::read_back
+    /// [`read_back_bytes_buffered`]: ReadBack::read_back_bytes_buffered
     fn read_back_bytes(self) -> ReadBackBytes<Self>
     where
         Self: Sized,
@@ -170,6 +291,36 @@ pub trait ReadBack {
         ReadBackBytes { inner: self }
     }
 
+    /// Transforms this `ReadBack` instance to an `Iterator` over its bytes, starting with the last byte.
+    ///
+    /// The reader is wrapped in a [`BufReadBacker`], so it isn't called for every byte. Use
+    /// [`BufReadBack::read_back_buf_bytes`] if the reader is buffered already.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use read_collection::ReadBack;
+    /// use std::fs::File;
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = File::open("log.txt")?;
+    ///     file.seek(SeekFrom::End(0))?;
+    ///
+    ///     let trailing_new_lines = file
+    ///         .read_back_bytes_buffered()
+    ///         .take_while(|byte| matches!(byte, Ok(b'\n')))
+    ///         .count();
+    ///     println!("{} trailing new lines", trailing_new_lines);
+    ///     Ok(())
+    /// }
+    /// ```
+    fn read_back_bytes_buffered(self) -> ReadBackBufBytes<BufReadBacker<Self>>
+    where
+        Self: Sized,
+    {
+        BufReadBacker::new(self).read_back_buf_bytes()
+    }
+
     /// Creates an adapter which will chain this stream with another.
     ///
     /// # Example
@@ -227,6 +378,36 @@ pub trait ReadBack {
     {
         ReadBackTake { inner: self, limit }
     }
+
+    /// Returns the bounds on the amount of bytes which can still be read back, like [`Iterator::size_hint`].
+    ///
+    /// The default implementation returns `(0, None)`, which is correct for any reader.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::ReadBack;
+    ///
+    /// fn main() {
+    ///     let data = [1, 2, 3];
+    ///
+    ///     assert_eq!(data.as_slice().read_back_size_hint(), (3, Some(3)));
+    ///     assert_eq!(data.as_slice().read_back_take(2).read_back_size_hint(), (2, Some(2)));
+    /// }
+    /// ```
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    /// Hints that the `amount` bytes in front of the current position are going to be read back soon.
+    ///
+    /// Readers can use it to fetch these bytes in the background, like a [`File`] does with `posix_fadvise` on
+    /// Linux. Kernels only read ahead for forward access by themselves. It's only a hint, so it doesn't change what
+    /// is read back and failures are ignored. The default implementation does nothing.
+    ///
+    /// [`File`]: std::fs::File
+    fn read_back_advise(&self, amount: usize) {
+        let _ = amount;
+    }
 }
 
 /// A `BufReadBack` is a type of [`ReadBack`]er which has an internal buffer, allowing it to perform extra ways of reading.
@@ -270,7 +451,7 @@ pub trait BufReadBack: ReadBack {
     /// so this functions returns `Result<bool>`, not `bool`.
     ///
     /// Default implementation calls [`read_back_fill_buf`] and checks that
-    /// returned slice is empty (which means that there is no data left,
+    /// returned slice is not empty (an empty slice means that there is no data left,
     /// since the start is reached).
     ///
     /// # Example
@@ -279,7 +460,7 @@ pub trait BufReadBack: ReadBack {
     /// [`ReadBack`]: ReadBack
     /// [`read_back_fill_buf`]: BufReadBack::read_back_fill_buf
     fn read_back_has_data_left(&mut self) -> io::Result<bool> {
-        self.read_back_fill_buf().map(|buffer| buffer.is_empty())
+        self.read_back_fill_buf().map(|buffer| !buffer.is_empty())
     }
 
     /// Read all bytes into `buf` until the delimiter `byte` or the beginning of the reader is reached.
@@ -357,7 +538,239 @@ pub trait BufReadBack: ReadBack {
     where
         Self: Sized,
     {
-        ReadBackLines { buf: self }
+        ReadBackLines {
+            buf: self,
+            amount_read: 0,
+        }
+    }
+
+    /// Transforms this `BufReadBack` instance to an `Iterator` over its bytes, starting with the last byte.
+    ///
+    /// Unlike [`ReadBack::read_back_bytes`], the bytes are taken from the end of [`read_back_fill_buf`], so the
+    /// reader is only called if the buffer is empty. Use [`ReadBack::read_back_bytes_buffered`] for unbuffered
+    /// readers.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::BufReadBack;
+    ///
+    /// fn main() {
+    ///     let data = [1, 2, 3];
+    ///
+    ///     let mut iterator = data.as_slice().read_back_buf_bytes();
+    ///     assert_eq!(iterator.size_hint(), (3, Some(3)));
+    ///
+    ///     let read_back_bytes = iterator
+    ///         .map(|b| b.unwrap())
+    ///         .collect::<Vec<u8>>();
+    ///
+    ///     assert_eq!(read_back_bytes, [3, 2, 1].to_vec());
+    /// }
+    /// ```
+    ///
+    /// [`read_back_fill_buf`]: BufReadBack::read_back_fill_buf
+    fn read_back_buf_bytes(self) -> ReadBackBufBytes<Self>
+    where
+        Self: Sized,
+    {
+        ReadBackBufBytes { inner: self }
+    }
+
+    /// Returns an iterator over the UTF-16 encoded lines of this reader, starting with the last line.
+    ///
+    /// The code units are counted from the current position, so it has to be at a code unit boundary, usually the
+    /// end of the source. Surrogate pairs which are split between two refills of the buffer are put together again.
+    ///
+    /// Like [`BufRead::lines`], the lines don't include their `\n` or `\r\n` and a new line at the end of the source
+    /// doesn't start another (empty) line. A byte order mark at the start of the source is removed from the first
+    /// line. Since it can't be read before the other lines, use [`Utf16Endianness::detect`] to find out the
+    /// endianness of a seekable source.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, Utf16Endianness};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let data: Vec<u8> = "\u{FEFF}first\r\nsecond 𝄞\n"
+    ///         .encode_utf16()
+    ///         .flat_map(u16::to_le_bytes)
+    ///         .collect();
+    ///     let endianness = Utf16Endianness::from_bom(&data).unwrap();
+    ///
+    ///     let lines = data
+    ///         .as_slice()
+    ///         .read_back_utf16_lines(endianness)
+    ///         .collect::<std::io::Result<Vec<String>>>()?;
+    ///
+    ///     assert_eq!(lines, ["second 𝄞", "first"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// The iterator returns an error of kind [`ErrorKind::InvalidData`] if a line isn't valid UTF-16 or if the source
+    /// has an odd amount of bytes.
+    ///
+    /// [`BufRead::lines`]: std::io::BufRead::lines
+    /// [`ErrorKind::InvalidData`]: std::io::ErrorKind::InvalidData
+    fn read_back_utf16_lines(self, endianness: Utf16Endianness) -> ReadBackUtf16Lines<Self>
+    where
+        Self: Sized,
+    {
+        ReadBackUtf16Lines::new(self, endianness)
+    }
+
+    /// Returns an iterator over the lines of this reader in the given `encoding`, transcoded to UTF-8 and starting
+    /// with the last line.
+    ///
+    /// It supports every ASCII compatible encoding, like Latin-1 (`windows-1252`), Shift_JIS or EUC-KR, since the
+    /// `\n` byte can't be part of a multi-byte sequence in them. The lines are split like in
+    /// [`read_back_utf16_lines`] and a byte order mark of `encoding` at the start of the source is removed.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{encoding_rs::SHIFT_JIS, BufReadBack};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let (data, _, _) = SHIFT_JIS.encode("最初\n二番目\n");
+    ///
+    ///     let lines = data
+    ///         .as_ref()
+    ///         .read_back_encoded_lines(SHIFT_JIS)?
+    ///         .collect::<std::io::Result<Vec<String>>>()?;
+    ///
+    ///     assert_eq!(lines, ["二番目", "最初"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error of kind [`ErrorKind::InvalidInput`] if the new lines of `encoding` can't be found
+    /// reliably by reading backwards. That's the case for UTF-16 (use [`read_back_utf16_lines`] instead),
+    /// ISO-2022-JP and the replacement encoding.
+    ///
+    /// The iterator returns an error of kind [`ErrorKind::InvalidData`] if a line is malformed in `encoding`.
+    ///
+    /// [`read_back_utf16_lines`]: BufReadBack::read_back_utf16_lines
+    /// [`ErrorKind::InvalidInput`]: std::io::ErrorKind::InvalidInput
+    /// [`ErrorKind::InvalidData`]: std::io::ErrorKind::InvalidData
+    #[cfg(feature = "encoding")]
+    fn read_back_encoded_lines(
+        self,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> io::Result<ReadBackEncodedLines<Self>>
+    where
+        Self: Sized,
+    {
+        ReadBackEncodedLines::new(self, encoding)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters (user-perceived characters) of this reader, starting
+    /// with the last one.
+    ///
+    /// The clusters are found with the rules of [Unicode Standard Annex #29], so combining marks, emoji ZWJ
+    /// sequences and flags stay together, even if they are split between two refills of the buffer.
+    ///
+    /// Requires the `graphemes` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::BufReadBack;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let text = "ne\u{301}e 🇩🇪👩‍👩‍👧";
+    ///
+    ///     let graphemes = text
+    ///         .as_bytes()
+    ///         .read_back_graphemes()
+    ///         .collect::<std::io::Result<Vec<String>>>()?;
+    ///
+    ///     assert_eq!(graphemes, ["👩‍👩‍👧", "🇩🇪", " ", "e", "e\u{301}", "n"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// The iterator returns an error of kind [`ErrorKind::InvalidData`] if the source isn't valid UTF-8.
+    ///
+    /// [Unicode Standard Annex #29]: https://www.unicode.org/reports/tr29/
+    /// [`ErrorKind::InvalidData`]: std::io::ErrorKind::InvalidData
+    #[cfg(feature = "graphemes")]
+    fn read_back_graphemes(self) -> ReadBackGraphemes<Self>
+    where
+        Self: Sized,
+    {
+        ReadBackGraphemes::new(self)
+    }
+
+    /// Searches backwards for the last occurrence of `needle` in front of the current position.
+    ///
+    /// Returns how many bytes in front of the current position the occurrence starts, or `None` if there's none.
+    /// [`BufReadBacker::rfind_offset`] returns the absolute offset in a seekable source instead.
+    /// The occurrence and everything behind it are consumed, so the reader is positioned at its start.
+    /// Occurrences which are split between two refills of the buffer are found, but the searched bytes aren't
+    /// collected, unlike in [`read_back_until`]. If `needle` is empty, `Some(0)` is returned.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, ReadBack};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut data = b"first --- second --- third".as_slice();
+    ///
+    ///     assert_eq!(data.rfind(b"---")?, Some(9));
+    ///
+    ///     let mut rest = Vec::new();
+    ///     data.read_back_to_end(&mut rest)?;
+    ///     assert_eq!(rest, b"first --- second ");
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the errors of [`read_back_fill_buf`], except the ones of kind [`ErrorKind::Interrupted`],
+    /// which are retried.
+    ///
+    /// [`read_back_until`]: BufReadBack::read_back_until
+    /// [`read_back_fill_buf`]: BufReadBack::read_back_fill_buf
+    /// [`ErrorKind::Interrupted`]: std::io::ErrorKind::Interrupted
+    fn rfind(&mut self, needle: &[u8]) -> Result<Option<u64>> {
+        let finder = memchr::memmem::FinderRev::new(needle);
+        rfind::rfind(self, &finder, &mut Vec::new())
+    }
+
+    /// Returns an iterator over the non-overlapping occurrences of `needle` in this reader, starting with the
+    /// last one.
+    ///
+    /// Each occurrence is reported like in [`rfind`], counted from the position where the search started.
+    /// Use [`BufReadBacker::rfind_offsets`] to get absolute offsets from a seekable source instead.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::BufReadBack;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let offsets = b"abcabcabc"
+    ///         .as_slice()
+    ///         .rfind_iter(b"bc")
+    ///         .collect::<std::io::Result<Vec<u64>>>()?;
+    ///
+    ///     assert_eq!(offsets, [2, 5, 8]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// The iterator returns the errors of [`rfind`] and stops afterwards.
+    ///
+    /// [`rfind`]: BufReadBack::rfind
+    fn rfind_iter(self, needle: &[u8]) -> ReadBackMatches<Self>
+    where
+        Self: Sized,
+    {
+        ReadBackMatches::new(self, needle)
     }
 }
 
@@ -373,6 +786,12 @@ pub struct ReadBackBytes<R> {
     inner: R,
 }
 
+impl<R> ReadBackBytes<R> {
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
 impl<R: ReadBack> Iterator for ReadBackBytes<R> {
     type Item = Result<u8>;
 
@@ -383,7 +802,6 @@ impl<R: ReadBack> Iterator for ReadBackBytes<R> {
         loop {
             return match self.inner.read_back(slice::from_mut(&mut byte)) {
                 Ok(0) => None,
-                Err(e) if e.kind() == ErrorKind::Other => None,
                 Ok(..) => Some(Ok(byte)),
                 Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
                 Err(e) => Some(Err(e)),
@@ -393,7 +811,49 @@ impl<R: ReadBack> Iterator for ReadBackBytes<R> {
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (0, None)
+        self.inner.read_back_size_hint()
+    }
+}
+
+/// An iterator over `u8` values of a buffered read-back reader, which takes them from its buffer.
+///
+/// This struct is generally created by calling [`read_back_buf_bytes`] on a [`BufReadBack`] reader or
+/// [`read_back_bytes_buffered`] on a [`ReadBack`] reader.
+/// Please see the documentation of [`read_back_buf_bytes`] for more details.
+///
+/// [`read_back_buf_bytes`]: BufReadBack::read_back_buf_bytes
+/// [`read_back_bytes_buffered`]: ReadBack::read_back_bytes_buffered
+#[derive(Debug)]
+pub struct ReadBackBufBytes<B> {
+    inner: B,
+}
+
+impl<B> ReadBackBufBytes<B> {
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: BufReadBack> Iterator for ReadBackBufBytes<B> {
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Result<u8>> {
+        loop {
+            return match self.inner.read_back_fill_buf() {
+                Ok(buf) => {
+                    let byte = *buf.last()?;
+                    self.inner.read_back_consume(1);
+                    Some(Ok(byte))
+                }
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => Some(Err(e)),
+            };
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.read_back_size_hint()
     }
 }
 
@@ -509,6 +969,22 @@ impl<T: ReadBack, U: ReadBack> ReadBack for ReadBackChain<T, U> {
         self.second.read_back_vectored(bufs)
     }
 
+    fn is_read_back_vectored(&self) -> bool {
+        self.first.is_read_back_vectored() || self.second.is_read_back_vectored()
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> Result<()> {
+        if !self.done_first {
+            let filled = buf.len();
+            self.first.read_back_buf(buf)?;
+            if buf.len() != filled || buf.remaining() == 0 {
+                return Ok(());
+            }
+            self.done_first = true;
+        }
+        self.second.read_back_buf(buf)
+    }
+
     fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
         let mut read = 0;
         if !self.done_first {
@@ -518,6 +994,16 @@ impl<T: ReadBack, U: ReadBack> ReadBack for ReadBackChain<T, U> {
         read += self.second.read_back_to_end(buf)?;
         Ok(read)
     }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        let second = self.second.read_back_size_hint();
+        if self.done_first {
+            return second;
+        }
+
+        let first = self.first.read_back_size_hint();
+        add_size_hints(first, second)
+    }
 }
 
 impl<T: BufReadBack, U: BufReadBack> BufReadBack for ReadBackChain<T, U> {
@@ -545,7 +1031,8 @@ impl<T: BufReadBack, U: BufReadBack> BufReadBack for ReadBackChain<T, U> {
             let n = self.first.read_back_until(byte, buf)?;
             read += n;
 
-            match buf.last() {
+            // the delimiter gets prepended, so it's the first byte if it has been found
+            match buf.first() {
                 Some(b) if *b == byte && n != 0 => return Ok(read),
                 _ => self.done_first = true,
             }
@@ -596,6 +1083,7 @@ impl<B: BufReadBack> Iterator for ReadBackSplit<B> {
 #[derive(Debug)]
 pub struct ReadBackLines<B> {
     buf: B,
+    amount_read: u64,
 }
 
 impl<B: BufReadBack> Iterator for ReadBackLines<B> {
@@ -605,7 +1093,9 @@ impl<B: BufReadBack> Iterator for ReadBackLines<B> {
         let mut buf = String::new();
         match self.buf.read_back_line(&mut buf) {
             Ok(0) => None,
-            Ok(_n) => {
+            Ok(n) => {
+                self.amount_read += n as u64;
+
                 if buf.starts_with('\n') {
                     buf = buf.drain(1..).collect();
                 } else if buf.starts_with("\r\n") {
@@ -614,7 +1104,18 @@ impl<B: BufReadBack> Iterator for ReadBackLines<B> {
 
                 Some(Ok(buf))
             }
-            Err(e) => Some(Err(e)),
+            Err(mut e) => {
+                // the offset should be relative to the position where the iteration started
+                if let Some(err) = e
+                    .get_mut()
+                    .and_then(|err| err.downcast_mut::<ReadBackError>())
+                {
+                    err.shift(self.amount_read);
+                    // the invalid line has been consumed, so the next lines are in front of it
+                    self.amount_read += err.amount_read();
+                }
+                Some(Err(e))
+            }
         }
     }
 }
@@ -666,6 +1167,22 @@ impl<T: ReadBack> ReadBack for ReadBackTake<T> {
         self.limit -= n as u64;
         Ok(n)
     }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        let limit = usize::try_from(self.limit).unwrap_or(usize::MAX);
+        let (lower, upper) = self.inner.read_back_size_hint();
+
+        let upper = match upper {
+            Some(upper) => cmp::min(upper, limit),
+            None => limit,
+        };
+        (cmp::min(lower, limit), Some(upper))
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        let limit = usize::try_from(self.limit).unwrap_or(usize::MAX);
+        self.inner.read_back_advise(cmp::min(amount, limit))
+    }
 }
 
 impl<T: BufReadBack> BufReadBack for ReadBackTake<T> {
@@ -697,48 +1214,59 @@ pub fn default_read_back_vectored<F: FnOnce(&mut [u8]) -> Result<usize>>(
 ) -> Result<usize> {
     let buf = bufs
         .iter_mut()
+        .rev()
         .find(|b| !b.is_empty())
         .map_or(&mut [][..], |b| &mut **b);
 
     read_back(buf)
 }
 
+pub fn default_read_back_buf<F: FnOnce(&mut [u8]) -> Result<usize>>(
+    read_back: F,
+    buf: &mut BorrowedBackBuf<'_>,
+) -> Result<()> {
+    let unfilled = buf.init_unfilled();
+    let len = unfilled.len();
+    let n = read_back(unfilled)?;
+
+    // the read-back bytes are at the start of `unfilled` but they belong right in front of the filled ones
+    unfilled.copy_within(..n, len - n);
+    buf.advance(n);
+    Ok(())
+}
+
 pub fn default_read_back_to_end<R: ReadBack + ?Sized>(
     reader: &mut R,
     dest_buf: &mut Vec<u8>,
 ) -> Result<usize> {
-    let mut buffers: Vec<Vec<u8>> = vec![];
-    let mut curr_buffer: Vec<u8> = vec![0; DEFAULT_BUF_SIZE];
-
+    let mut buffer = RevBuf::with_tail(std::mem::take(dest_buf));
     let mut amount_read: usize = 0;
 
     loop {
-        match reader.read_back(curr_buffer.as_mut_slice()) {
-            Ok(amount) => {
-                println!("{}", amount);
-                if amount == 0 {
-                    let mut final_buf = Vec::with_capacity(amount_read + dest_buf.len());
-
-                    for buffer in buffers.into_iter().rev() {
-                        final_buf.extend_from_slice(&buffer);
-                    }
-                    final_buf.extend_from_slice(dest_buf);
-                    *dest_buf = final_buf;
+        // `front` is growing with the buffer, so the amount of `read_back` calls stays logarithmic
+        // for sources which can fill the whole slice.
+        let front = buffer.front_mut(DEFAULT_BUF_SIZE);
+        let front_len = front.len();
 
-                    return Ok(amount_read);
-                }
-                curr_buffer = {
-                    let curr_buffer_len = curr_buffer.len();
-                    curr_buffer[curr_buffer_len - amount..].to_vec()
-                };
+        match reader.read_back(front) {
+            Ok(0) => break,
+            Ok(amount) => {
+                // the read-back bytes are at the start of `front` but they belong right in front
+                // of the already stored bytes
+                front.copy_within(..amount, front_len - amount);
+                buffer.commit_front(amount);
                 amount_read += amount;
-                buffers.push(curr_buffer);
-                curr_buffer = Vec::new();
             }
             Err(e) if e.kind() == ErrorKind::Interrupted => continue,
-            Err(e) => return Err(e),
+            Err(e) => {
+                *dest_buf = buffer.into_vec();
+                return Err(e);
+            }
         }
     }
+
+    *dest_buf = buffer.into_vec();
+    Ok(amount_read)
 }
 
 fn default_read_back_to_string<R: ReadBack + ?Sized>(r: &mut R, buf: &mut String) -> Result<usize> {
@@ -746,9 +1274,11 @@ fn default_read_back_to_string<R: ReadBack + ?Sized>(r: &mut R, buf: &mut String
     let amount_bytes = default_read_back_to_end(r, &mut bytes_buf)?;
 
     let mut read_back_string = String::from_utf8(bytes_buf).map_err(|e| {
-        std::io::Error::new(
-            ErrorKind::InvalidData,
-            format!("Couldn't convert the read-back content to a string: {}", e),
+        ReadBackError::invalid_utf8(
+            ReadBackOperation::ReadBackToString,
+            e.as_bytes(),
+            e.utf8_error(),
+            true,
         )
     })?;
 
@@ -763,7 +1293,9 @@ fn default_read_back_exact<R: ReadBack + ?Sized>(r: &mut R, mut buf: &mut [u8])
         match r.read_back(buf) {
             Ok(0) => break,
             Ok(n) => {
+                // the bytes which are read back belong to the end of the remaining buffer
                 let buf_len = buf.len();
+                buf.copy_within(..n, buf_len - n);
                 buf = &mut buf[..buf_len - n];
             }
             Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
@@ -786,6 +1318,7 @@ fn default_buf_read_back_until<R: BufReadBack + ?Sized>(
     delim: u8,
     buf: &mut Vec<u8>,
 ) -> io::Result<usize> {
+    let mut buffer = RevBuf::with_tail(std::mem::take(buf));
     let mut amount_read = 0;
 
     loop {
@@ -793,25 +1326,18 @@ fn default_buf_read_back_until<R: BufReadBack + ?Sized>(
             let new_read = match r.read_back_fill_buf() {
                 Ok(n) => n,
                 Err(err) if err.kind() == ErrorKind::Interrupted => continue,
-                Err(err) => return Err(err),
+                Err(err) => {
+                    *buf = buffer.into_vec();
+                    return Err(err);
+                }
             };
             match memchr::memrchr(delim, new_read) {
                 Some(index) => {
-                    let used = new_read.len() - index;
-
-                    let mut new_buf = Vec::with_capacity(buf.len() + used);
-                    new_buf.extend_from_slice(&new_read[index..]);
-                    new_buf.extend_from_slice(buf);
-                    *buf = new_buf;
-
-                    (true, used)
+                    buffer.prepend(&new_read[index..]);
+                    (true, new_read.len() - index)
                 }
                 None => {
-                    let mut new_buf = Vec::with_capacity(buf.len() + new_read.len());
-                    new_buf.extend_from_slice(new_read);
-                    new_buf.extend_from_slice(buf);
-                    *buf = new_buf;
-
+                    buffer.prepend(new_read);
                     (false, new_read.len())
                 }
             }
@@ -820,6 +1346,7 @@ fn default_buf_read_back_until<R: BufReadBack + ?Sized>(
         r.read_back_consume(used);
         amount_read += used;
         if done || used == 0 {
+            *buf = buffer.into_vec();
             return Ok(amount_read);
         }
     }
@@ -854,18 +1381,18 @@ fn default_buf_read_back_line<R: BufReadBack + ?Sized>(
     r: &mut R,
     dest: &mut String,
 ) -> io::Result<usize> {
-    let mut buffer = Vec::with_capacity(crate::DEFAULT_BUF_SIZE);
+    let mut buffer = Vec::new();
 
     let mut amount_read = r.read_back_until(b'\n', &mut buffer)?;
-    if r.read_back_fill_buf()?
-        .last()
-        .map(|&c| c == b'\r')
-        .unwrap_or(false)
-    {
-        let mut new_buf = Vec::with_capacity(buffer.len() + 1);
-        new_buf.push(b'\r');
-        new_buf.extend_from_slice(&buffer);
-        buffer = new_buf;
+    let carriage_return = loop {
+        match r.read_back_fill_buf() {
+            Ok(new_read) => break new_read.last() == Some(&b'\r'),
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    };
+    if carriage_return {
+        buffer.insert(0, b'\r');
         amount_read += 1;
         r.read_back_consume(1);
     }
@@ -877,6 +1404,28 @@ fn default_buf_read_back_line<R: BufReadBack + ?Sized>(
 
             Ok(amount_read)
         }
-        Err(err) => Err(io::Error::new(ErrorKind::InvalidData, err)),
+        Err(err) => {
+            // checking the start may fail, but that mustn't hide the invalid UTF-8
+            let at_start = matches!(r.read_back_has_data_left(), Ok(false));
+            Err(ReadBackError::invalid_utf8(
+                ReadBackOperation::ReadBackLine,
+                err.as_bytes(),
+                err.utf8_error(),
+                at_start,
+            )
+            .into())
+        }
     }
 }
+
+/// Adds the size hints of two readers which are read back one after the other.
+pub(crate) fn add_size_hints(
+    (lower, upper): (usize, Option<usize>),
+    (other_lower, other_upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let upper = match (upper, other_upper) {
+        (Some(upper), Some(other_upper)) => upper.checked_add(other_upper),
+        _ => None,
+    };
+    (lower.saturating_add(other_lower), upper)
+}
@@ -0,0 +1,356 @@
+use std::{
+    io::{self, ErrorKind},
+    panic,
+    sync::mpsc::{self, Receiver, Sender, SyncSender},
+    thread::{self, JoinHandle},
+};
+
+use crate::{BufReadBack, ReadBack};
+
+/// The default size of the chunks which are read by the worker thread of a [`PrefetchingReadBacker`].
+pub const DEFAULT_PREFETCH_SIZE: usize = 256 * 1024;
+
+/// A buffered [`ReadBack`]er which reads the next chunk on a worker thread while the current one is processed.
+///
+/// [`BufReadBacker`] reads a new chunk only once its buffer is empty, so reading and processing take turns. For
+/// sources with a high latency, like cold files on spinning disks, `PrefetchingReadBacker` moves the reader to a
+/// worker thread instead, which reads the chunk in front of the current one in the meantime (double-buffering).
+///
+/// The worker stops at the start of the reader. If the `PrefetchingReadBacker` is dropped earlier, the worker
+/// stops after its current read.
+///
+/// # Example
+/// ```
+/// use read_collection::{BufReadBack, PrefetchingReadBacker};
+/// use std::io::{Seek, SeekFrom, Write};
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = tempfile::tempfile()?;
+///     file.write_all(b"first\nsecond\nthird")?;
+///     file.seek(SeekFrom::End(0))?;
+///
+///     let reader = PrefetchingReadBacker::new(file)?;
+///     let lines = reader.read_back_lines().collect::<std::io::Result<Vec<String>>>()?;
+///
+///     assert_eq!(lines, ["third", "second", "first"]);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`BufReadBacker`]: crate::BufReadBacker
+#[derive(Debug)]
+pub struct PrefetchingReadBacker<R> {
+    /// The unconsumed bytes of the current chunk.
+    chunk: Vec<u8>,
+    /// The chunks of the worker. It's disconnected once the worker reached the start of the reader.
+    chunks: Option<Receiver<io::Result<Vec<u8>>>>,
+    /// The chunks which have been processed, so the worker can reuse their allocation.
+    recycled: Sender<Vec<u8>>,
+    worker: Option<JoinHandle<R>>,
+    /// The reader, once the worker has finished.
+    inner: Option<R>,
+    /// Whether the worker has panicked, so the rest of the reader can't be read back anymore.
+    panicked: bool,
+    capacity: usize,
+}
+
+impl<R: ReadBack + Send + 'static> PrefetchingReadBacker<R> {
+    /// Creates a new `PrefetchingReadBacker` with chunks of [`DEFAULT_PREFETCH_SIZE`] bytes and starts its worker.
+    ///
+    /// # Errors
+    /// Returns the error if the worker thread can't be spawned.
+    pub fn new(inner: R) -> io::Result<Self> {
+        Self::with_capacity(DEFAULT_PREFETCH_SIZE, inner)
+    }
+
+    /// Creates a new `PrefetchingReadBacker` with chunks of `capacity` bytes and starts its worker.
+    ///
+    /// At most three chunks are in memory at the same time: the current one, the prefetched one and the one which
+    /// is being read.
+    ///
+    /// # Errors
+    /// Returns the error if the worker thread can't be spawned.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{PrefetchingReadBacker, ReadBack};
+    /// use std::io::{Seek, SeekFrom, Write};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = tempfile::tempfile()?;
+    ///     file.write_all(b"some content")?;
+    ///     file.seek(SeekFrom::End(0))?;
+    ///
+    ///     let mut reader = PrefetchingReadBacker::with_capacity(4, file)?;
+    ///     let mut content = Vec::new();
+    ///     reader.read_back_to_end(&mut content)?;
+    ///
+    ///     assert_eq!(content, b"some content");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_capacity(capacity: usize, inner: R) -> io::Result<Self> {
+        // a rendezvous channel, so the worker reads only one chunk ahead
+        let (chunks_tx, chunks) = mpsc::sync_channel(0);
+        let (recycled, recycled_rx) = mpsc::channel();
+
+        let worker = thread::Builder::new()
+            .name("read-back-prefetch".to_string())
+            .spawn(move || prefetch(inner, capacity, chunks_tx, recycled_rx))?;
+
+        Ok(Self {
+            chunk: Vec::new(),
+            chunks: Some(chunks),
+            recycled,
+            worker: Some(worker),
+            inner: None,
+            panicked: false,
+            capacity,
+        })
+    }
+}
+
+impl<R> PrefetchingReadBacker<R> {
+    /// Returns the size of the chunks which are read by the worker.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the unconsumed bytes of the current chunk.
+    ///
+    /// Unlike [`read_back_fill_buf`], this will not wait for the next chunk if it is empty.
+    ///
+    /// [`read_back_fill_buf`]: BufReadBack::read_back_fill_buf
+    pub fn buffer(&self) -> &[u8] {
+        &self.chunk
+    }
+
+    /// Stops the worker and returns the underlying reader.
+    ///
+    /// This waits until the current read of the worker is done. The buffered and prefetched chunks are lost, so
+    /// the reader is positioned in front of them.
+    ///
+    /// # Panics
+    /// Panics if the worker has panicked.
+    pub fn into_inner(mut self) -> R {
+        if let Some(inner) = self.inner.take() {
+            return inner;
+        }
+        if self.panicked {
+            panic!("the prefetching thread panicked");
+        }
+
+        // the worker stops once it can't send its chunk anymore
+        drop(self.chunks.take());
+        let worker = self.worker.take().expect("the worker is only taken once");
+        match worker.join() {
+            Ok(inner) => inner,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+
+    /// Waits for the next chunk of the worker.
+    fn next_chunk(&mut self) -> io::Result<()> {
+        if self.panicked {
+            return Err(panicked());
+        }
+        let received = match &self.chunks {
+            Some(chunks) => chunks.recv(),
+            None => return Ok(()),
+        };
+
+        match received {
+            Ok(Ok(chunk)) => {
+                let processed = std::mem::replace(&mut self.chunk, chunk);
+                // the worker might have finished already
+                let _ = self.recycled.send(processed);
+                Ok(())
+            }
+            Ok(Err(err)) => Err(err),
+            // the worker has reached the start of the reader
+            Err(mpsc::RecvError) => {
+                self.chunks = None;
+                let worker = self.worker.take().expect("the worker is only taken once");
+                match worker.join() {
+                    Ok(inner) => {
+                        self.inner = Some(inner);
+                        Ok(())
+                    }
+                    Err(_) => {
+                        // the chunks in front of the panic are lost, so don't report the start of the reader later
+                        self.panicked = true;
+                        Err(panicked())
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn panicked() -> io::Error {
+    io::Error::other("the prefetching thread panicked")
+}
+
+/// The loop of the worker: reads back chunks of `capacity` bytes until the start of `inner` or until the
+/// receiver is dropped.
+fn prefetch<R: ReadBack>(
+    mut inner: R,
+    capacity: usize,
+    chunks: SyncSender<io::Result<Vec<u8>>>,
+    recycled: Receiver<Vec<u8>>,
+) -> R {
+    loop {
+        let mut chunk = recycled.try_recv().unwrap_or_default();
+        chunk.resize(capacity, 0);
+
+        let read = loop {
+            match inner.read_back(&mut chunk) {
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                read => break read,
+            }
+        };
+
+        let message = match read {
+            Ok(0) => return inner,
+            Ok(amount) => {
+                chunk.truncate(amount);
+                Ok(chunk)
+            }
+            // the next chunk is a new attempt, like the next call of `read_back_fill_buf` of `BufReadBacker`
+            Err(err) => Err(err),
+        };
+
+        if chunks.send(message).is_err() {
+            return inner;
+        }
+    }
+}
+
+impl<R> ReadBack for PrefetchingReadBacker<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut rem = self.read_back_fill_buf()?;
+        let nread = rem.read_back(buf)?;
+        self.read_back_consume(nread);
+        Ok(nread)
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        if self.chunks.is_none() {
+            (self.chunk.len(), Some(self.chunk.len()))
+        } else {
+            (self.chunk.len(), None)
+        }
+    }
+}
+
+impl<R> BufReadBack for PrefetchingReadBacker<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.chunk.is_empty() {
+            self.next_chunk()?;
+        }
+        Ok(&self.chunk)
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        let amt = std::cmp::min(amt, self.chunk.len());
+        self.chunk.truncate(self.chunk.len() - amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, FailingReadBack};
+    use std::io::{Seek, SeekFrom, Write};
+
+    /// The worker needs a `'static` reader, so it owns its content.
+    struct Owned(Vec<u8>);
+
+    impl ReadBack for Owned {
+        fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let amount = self.0.as_slice().read_back(buf)?;
+            self.0.truncate(self.0.len() - amount);
+            Ok(amount)
+        }
+
+        fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+            (self.0.len(), Some(self.0.len()))
+        }
+    }
+
+    fn owned(content: &[u8]) -> Owned {
+        Owned(content.to_vec())
+    }
+
+    #[test]
+    fn conformance() {
+        testing::check_buf_read_back_with_config(
+            proptest::test_runner::Config::with_cases(64),
+            |content| Box::new(PrefetchingReadBacker::with_capacity(5, owned(content)).unwrap()),
+        );
+    }
+
+    #[test]
+    fn file() {
+        let content: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&content).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+
+        let mut reader = PrefetchingReadBacker::with_capacity(4096, file).unwrap();
+        let mut read = Vec::new();
+        reader.read_back_to_end(&mut read).unwrap();
+        assert_eq!(read, content);
+
+        let mut file = reader.into_inner();
+        assert_eq!(file.stream_position().unwrap(), 0);
+    }
+
+    #[test]
+    fn errors_are_forwarded() {
+        let inner = FailingReadBack::new(owned(b"abcdef"), 3);
+        let mut reader = PrefetchingReadBacker::with_capacity(3, inner).unwrap();
+
+        assert_eq!(reader.read_back_fill_buf().unwrap(), b"def");
+        reader.read_back_consume(3);
+        assert_eq!(
+            reader.read_back_fill_buf().unwrap_err().kind(),
+            ErrorKind::Other
+        );
+    }
+
+    /// Panics when it's read back.
+    struct PanickingReadBack;
+
+    impl ReadBack for PanickingReadBack {
+        fn read_back(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("failed to read back");
+        }
+    }
+
+    #[test]
+    fn worker_panic_is_no_end() {
+        let inner = owned(b"abc").read_back_chain(PanickingReadBack);
+        let mut reader = PrefetchingReadBacker::with_capacity(3, inner).unwrap();
+
+        assert_eq!(reader.read_back_fill_buf().unwrap(), b"abc");
+        reader.read_back_consume(3);
+        for _ in 0..3 {
+            assert_eq!(
+                reader.read_back_fill_buf().unwrap_err().kind(),
+                ErrorKind::Other
+            );
+        }
+    }
+
+    #[test]
+    fn into_inner_stops_the_worker() {
+        let reader = PrefetchingReadBacker::with_capacity(2, owned(b"abcdefgh")).unwrap();
+        let mut inner = reader.into_inner();
+
+        // the worker has prefetched at most one chunk, which is lost
+        let mut rest = Vec::new();
+        inner.read_back_to_end(&mut rest).unwrap();
+        assert!(rest == b"abcdef" || rest == b"abcdefgh", "{:?}", rest);
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::{self, IoSliceMut};
+
+use crate::{read_back::add_size_hints, BorrowedBackBuf, BufReadBack, ReadBack};
+
+/// Reader adapter which chains any amount of readers.
+///
+/// It behaves like nested [`read_back_chain`] calls: The first reader is read back first and once it's
+/// exhausted, the next one is read back. Readers which have been read back completely are dropped.
+///
+/// The readers don't need to have the same type if they are boxed, like `Box<dyn BufReadBack>`.
+///
+/// # Example
+/// ```
+/// use read_collection::{BufReadBack, ReadBackConcat};
+///
+/// fn main() -> std::io::Result<()> {
+///     let newest = b"4\n5\n".as_slice();
+///     let older = b"2\n3\n".as_slice();
+///     let oldest = b"1\n".as_slice();
+///
+///     let concat = ReadBackConcat::new([newest, older, oldest]);
+///     let lines = concat
+///         .read_back_split(b'\n')
+///         .collect::<std::io::Result<Vec<Vec<u8>>>>()?;
+///
+///     let expected: [&[u8]; 6] = [b"", b"5", b"4", b"3", b"2", b"1"];
+///     assert_eq!(lines, expected);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`read_back_chain`]: ReadBack::read_back_chain
+#[derive(Debug)]
+pub struct ReadBackConcat<R> {
+    /// The remaining readers in reverse order, so the current one is the last one.
+    readers: Vec<R>,
+}
+
+impl<R> ReadBackConcat<R> {
+    /// Creates a new `ReadBackConcat` which reads back `readers` in the given order.
+    pub fn new<I: IntoIterator<Item = R>>(readers: I) -> Self {
+        let mut readers: Vec<R> = readers.into_iter().collect();
+        readers.reverse();
+        Self { readers }
+    }
+
+    /// Consumes the [`ReadBackConcat`], returning the readers which haven't been read back completely yet.
+    ///
+    /// The first one is the reader which is currently read back.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{ReadBack, ReadBackConcat};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut concat = ReadBackConcat::new([b"ab".as_slice(), b"cd", b"e"]);
+    ///
+    ///     let mut buffer = [0; 3];
+    ///     concat.read_back_exact(&mut buffer)?;
+    ///     assert_eq!(&buffer, b"dab");
+    ///
+    ///     let expected: [&[u8]; 2] = [b"c", b"e"];
+    ///     assert_eq!(concat.into_inner(), expected);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_inner(self) -> Vec<R> {
+        let mut readers = self.readers;
+        readers.reverse();
+        readers
+    }
+
+    /// Gets references to the readers which haven't been read back completely yet, starting with the current one.
+    pub fn get_ref(&self) -> impl ExactSizeIterator<Item = &R> + DoubleEndedIterator {
+        self.readers.iter().rev()
+    }
+
+    /// Gets mutable references to the readers which haven't been read back completely yet, starting with the
+    /// current one.
+    ///
+    /// Care should be taken to avoid modifying the internal I/O state of the
+    /// underlying readers as doing so may corrupt the internal state of this
+    /// [`ReadBackConcat`].
+    pub fn get_mut(&mut self) -> impl ExactSizeIterator<Item = &mut R> + DoubleEndedIterator {
+        self.readers.iter_mut().rev()
+    }
+}
+
+impl<R> FromIterator<R> for ReadBackConcat<R> {
+    fn from_iter<I: IntoIterator<Item = R>>(readers: I) -> Self {
+        Self::new(readers)
+    }
+}
+
+impl<R: ReadBack> ReadBack for ReadBackConcat<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while let Some(reader) = self.readers.last_mut() {
+            match reader.read_back(buf)? {
+                0 if !buf.is_empty() => self.readers.pop(),
+                n => return Ok(n),
+            };
+        }
+        Ok(0)
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        while let Some(reader) = self.readers.last_mut() {
+            match reader.read_back_vectored(bufs)? {
+                0 if bufs.iter().any(|b| !b.is_empty()) => self.readers.pop(),
+                n => return Ok(n),
+            };
+        }
+        Ok(0)
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        self.readers.iter().any(ReadBack::is_read_back_vectored)
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> io::Result<()> {
+        while let Some(reader) = self.readers.last_mut() {
+            let filled = buf.len();
+            reader.read_back_buf(buf)?;
+            if buf.len() != filled || buf.remaining() == 0 {
+                return Ok(());
+            }
+            self.readers.pop();
+        }
+        Ok(())
+    }
+
+    fn read_back_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let mut read = 0;
+        while let Some(reader) = self.readers.last_mut() {
+            read += reader.read_back_to_end(buf)?;
+            self.readers.pop();
+        }
+        Ok(read)
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        self.readers
+            .iter()
+            .map(ReadBack::read_back_size_hint)
+            .fold((0, Some(0)), add_size_hints)
+    }
+}
+
+impl<R: BufReadBack> BufReadBack for ReadBackConcat<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        while let Some(reader) = self.readers.last_mut() {
+            if !reader.read_back_fill_buf()?.is_empty() {
+                break;
+            }
+            self.readers.pop();
+        }
+
+        match self.readers.last_mut() {
+            Some(reader) => reader.read_back_fill_buf(),
+            None => Ok(&[]),
+        }
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        if let Some(reader) = self.readers.last_mut() {
+            reader.read_back_consume(amt)
+        }
+    }
+
+    fn read_back_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let mut read = 0;
+        while let Some(reader) = self.readers.last_mut() {
+            let n = reader.read_back_until(byte, buf)?;
+            read += n;
+
+            // the delimiter gets prepended, so it's the first byte if it has been found
+            match buf.first() {
+                Some(b) if *b == byte && n != 0 => return Ok(read),
+                _ => self.readers.pop(),
+            };
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing, BufReadBacker};
+
+    #[test]
+    fn conformance() {
+        testing::check_buf_read_back(|content| {
+            // split the content into unevenly sized readers, including empty ones
+            let mut readers: Vec<Box<dyn BufReadBack + '_>> = Vec::new();
+            let mut rest = content;
+            let mut size = 0;
+            while !rest.is_empty() {
+                let (start, end) = rest.split_at(rest.len().saturating_sub(size));
+                readers.push(Box::new(BufReadBacker::with_capacity(3, end)));
+                rest = start;
+                size = size * 2 + 1;
+            }
+            readers.push(Box::new(b"".as_slice()));
+
+            Box::new(ReadBackConcat::new(readers))
+        });
+    }
+
+    #[test]
+    fn read_back_until_across_readers() {
+        let mut concat = ReadBackConcat::new([b"c".as_slice(), b"b", b"", b"\na"]);
+        let mut buffer = Vec::new();
+
+        assert_eq!(concat.read_back_until(b'\n', &mut buffer).ok(), Some(4));
+        assert_eq!(buffer, b"\nabc");
+        assert_eq!(concat.get_ref().len(), 1);
+
+        buffer.clear();
+        assert_eq!(concat.read_back_until(b'\n', &mut buffer).ok(), Some(0));
+        assert_eq!(concat.get_ref().len(), 0);
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+};
+
+use crate::{BufReadBack, BufReadBacker, ReadBack};
+
+/// A line which has been found by [`ReadBackGrep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepLine {
+    /// The position of the line in the source.
+    pub offset: u64,
+    /// The content of the line, without its `\n` or `\r\n`.
+    pub data: Vec<u8>,
+    /// Whether the line is a match or only context.
+    pub is_match: bool,
+}
+
+/// Matching lines together with their context, see [`ReadBackGrep`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepGroup {
+    /// The lines in the order of the source.
+    pub lines: Vec<GrepLine>,
+}
+
+/// An iterator over the lines which match a predicate, together with the lines around them, starting with the
+/// last match. It's the backwards version of `grep -B <before> -A <after>`.
+///
+/// The search starts at the current position of the reader, which should be the start of a line or the end of
+/// the source. Each match is returned with up to `before` lines in front of it and up to `after` lines behind
+/// it. The lines behind the matches close to the start of the search are read forwards, so they may come from
+/// behind the position where the search started. If the context of two matches overlaps or touches, they are
+/// returned in the same [`GrepGroup`].
+///
+/// # Example
+/// ```
+/// use read_collection::ReadBackGrep;
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = tempfile::tempfile()?;
+///     file.write_all(b"start\nERROR a\nretry\nok\nok\nok\nERROR b\nretry\nERROR c\nstop\n")?;
+///
+///     let groups = ReadBackGrep::new(file, |line| line.starts_with(b"ERROR"))?
+///         .with_context(1, 1)
+///         .collect::<std::io::Result<Vec<_>>>()?;
+///
+///     let lines: Vec<Vec<&[u8]>> = groups
+///         .iter()
+///         .map(|group| group.lines.iter().map(|line| line.data.as_slice()).collect())
+///         .collect();
+///     let expected: [&[&[u8]]; 2] = [
+///         &[b"ok", b"ERROR b", b"retry", b"ERROR c", b"stop"],
+///         &[b"start", b"ERROR a", b"retry"],
+///     ];
+///     assert_eq!(lines, expected);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReadBackGrep<R, F> {
+    reader: BufReadBacker<R>,
+    predicate: F,
+    before: usize,
+    after: usize,
+    max_matches: usize,
+    matches: usize,
+    /// The position where the search started.
+    start: u64,
+    /// The position up to which the lines haven't been read back yet.
+    pos: u64,
+    /// The lines which have been read back, but aren't part of a group yet, in the order of the source.
+    /// They are kept as the context behind the next match.
+    pending: VecDeque<GrepLine>,
+    /// Whether the last line which has been read back starts with a `\n`, which terminates the next line.
+    terminates_next: bool,
+    started: bool,
+    read_forward: bool,
+    done: bool,
+}
+
+impl<R: ReadBack + Read + Seek, F: FnMut(&[u8]) -> bool> ReadBackGrep<R, F> {
+    /// Creates a new `ReadBackGrep` which searches backwards from the current position of `reader` for the lines
+    /// which fulfil `predicate`.
+    ///
+    /// There's no context by default, see [`with_context`].
+    ///
+    /// # Errors
+    /// Returns the error if the position of `reader` can't be determined.
+    ///
+    /// [`with_context`]: ReadBackGrep::with_context
+    pub fn new(mut reader: R, predicate: F) -> io::Result<Self> {
+        let start = reader.stream_position()?;
+
+        Ok(Self {
+            reader: BufReadBacker::new(reader),
+            predicate,
+            before: 0,
+            after: 0,
+            max_matches: usize::MAX,
+            matches: 0,
+            start,
+            pos: start,
+            pending: VecDeque::new(),
+            terminates_next: false,
+            started: false,
+            read_forward: false,
+            done: false,
+        })
+    }
+}
+
+impl<R, F> ReadBackGrep<R, F> {
+    /// Sets how many lines in front of and behind each match are returned as its context.
+    pub fn with_context(mut self, before: usize, after: usize) -> Self {
+        self.before = before;
+        self.after = after;
+        self
+    }
+
+    /// Stops the search after `max_matches` matches, like `grep -m`.
+    ///
+    /// The context in front of the last match isn't searched anymore, so its lines are never marked as matches.
+    pub fn with_max_matches(mut self, max_matches: usize) -> Self {
+        self.max_matches = max_matches;
+        self
+    }
+
+    /// Returns the position up to which the lines haven't been read back yet.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: ReadBack + Read + Seek, F: FnMut(&[u8]) -> bool> ReadBackGrep<R, F> {
+    fn read_back_line(&mut self) -> io::Result<Option<GrepLine>> {
+        loop {
+            let mut data = Vec::new();
+            let amount = self.reader.read_back_until(b'\n', &mut data)?;
+            if amount == 0 {
+                return Ok(None);
+            }
+            self.pos -= amount as u64;
+
+            let terminated = self.terminates_next;
+            self.terminates_next = data.first() == Some(&b'\n');
+            let offset = if self.terminates_next {
+                data.remove(0);
+                self.pos + 1
+            } else {
+                self.pos
+            };
+
+            // the search starts behind a new line, so there's no line in front of it
+            if !std::mem::replace(&mut self.started, true) && data.is_empty() {
+                continue;
+            }
+
+            if terminated && data.last() == Some(&b'\r') {
+                data.pop();
+            }
+            return Ok(Some(GrepLine {
+                offset,
+                data,
+                is_match: false,
+            }));
+        }
+    }
+
+    /// Reads up to `amount` lines forwards from the position where the search started.
+    fn read_forward(&mut self, amount: usize) -> io::Result<Vec<GrepLine>> {
+        let reader = self.reader.get_mut();
+        let resume = reader.stream_position()?;
+        reader.seek(SeekFrom::Start(self.start))?;
+
+        let mut forward = BufReader::new(&mut *reader);
+        let mut lines = Vec::new();
+        let mut offset = self.start;
+        while lines.len() < amount {
+            let mut data = Vec::new();
+            let read = forward.read_until(b'\n', &mut data)?;
+            if read == 0 {
+                break;
+            }
+
+            if data.last() == Some(&b'\n') {
+                data.pop();
+                if data.last() == Some(&b'\r') {
+                    data.pop();
+                }
+            }
+            lines.push(GrepLine {
+                offset,
+                data,
+                is_match: false,
+            });
+            offset += read as u64;
+        }
+
+        // the buffered bytes of `self.reader` are in front of `resume`, so they are still valid
+        drop(forward);
+        reader.seek(SeekFrom::Start(resume))?;
+        Ok(lines)
+    }
+
+    fn next_group(&mut self) -> io::Result<Option<GrepGroup>> {
+        if self.matches >= self.max_matches {
+            return Ok(None);
+        }
+
+        let found = loop {
+            let mut line = match self.read_back_line()? {
+                Some(line) => line,
+                None => return Ok(None),
+            };
+            if (self.predicate)(&line.data) {
+                line.is_match = true;
+                break line;
+            }
+            self.pending.push_front(line);
+            self.pending.truncate(self.after);
+        };
+        self.matches += 1;
+
+        // only the lines behind the first match can be behind the position where the search started
+        if !std::mem::replace(&mut self.read_forward, true) && self.pending.len() < self.after {
+            let forward = self.read_forward(self.after - self.pending.len())?;
+            self.pending.extend(forward);
+        }
+
+        let mut lines = std::mem::take(&mut self.pending);
+        lines.push_front(found);
+
+        // a match within `before + after + 1` lines has an overlapping or touching context, so it's merged
+        let mut lookahead = Vec::new();
+        while lookahead.len() <= self.before + self.after {
+            let mut line = match self.read_back_line()? {
+                Some(line) => line,
+                None => break,
+            };
+
+            if self.matches < self.max_matches && (self.predicate)(&line.data) {
+                line.is_match = true;
+                self.matches += 1;
+                for context in lookahead.drain(..) {
+                    lines.push_front(context);
+                }
+                lines.push_front(line);
+            } else {
+                lookahead.push(line);
+            }
+        }
+
+        let rest = lookahead.split_off(std::cmp::min(self.before, lookahead.len()));
+        for context in lookahead {
+            lines.push_front(context);
+        }
+        for line in rest {
+            self.pending.push_front(line);
+        }
+        self.pending.truncate(self.after);
+
+        Ok(Some(GrepGroup {
+            lines: lines.into(),
+        }))
+    }
+}
+
+impl<R: ReadBack + Read + Seek, F: FnMut(&[u8]) -> bool> Iterator for ReadBackGrep<R, F> {
+    type Item = io::Result<GrepGroup>;
+
+    fn next(&mut self) -> Option<io::Result<GrepGroup>> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_group() {
+            Ok(Some(group)) => Some(Ok(group)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::Write};
+
+    fn file(content: &str) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    fn is_match(line: &[u8]) -> bool {
+        line.starts_with(b"x")
+    }
+
+    /// The expected groups, found by going forwards through all lines.
+    fn forwards(content: &str, before: usize, after: usize, max_matches: usize) -> Vec<GrepGroup> {
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            let data = line.strip_suffix('\n').unwrap_or(line);
+            let data = data.strip_suffix('\r').unwrap_or(data);
+            lines.push(GrepLine {
+                offset,
+                data: data.as_bytes().to_vec(),
+                is_match: false,
+            });
+            offset += line.len() as u64;
+        }
+
+        let mut matches: Vec<usize> = (0..lines.len())
+            .filter(|&i| is_match(&lines[i].data))
+            .collect();
+        matches.drain(..matches.len().saturating_sub(max_matches));
+
+        // the groups are the runs of lines which are in the context of a match
+        let mut groups: Vec<GrepGroup> = Vec::new();
+        let mut in_group = false;
+        for (i, line) in lines.into_iter().enumerate() {
+            let in_context = matches.iter().any(|&m| i + before >= m && i <= m + after);
+            if !in_context {
+                in_group = false;
+                continue;
+            }
+            if !in_group {
+                groups.push(GrepGroup { lines: Vec::new() });
+                in_group = true;
+            }
+            groups.last_mut().unwrap().lines.push(GrepLine {
+                is_match: matches.contains(&i),
+                ..line
+            });
+        }
+
+        groups.reverse();
+        groups
+    }
+
+    #[test]
+    fn same_as_forwards() {
+        let contents = [
+            "",
+            "x\n",
+            "x no trailing new line",
+            "a\nx\nb\nc\nd\ne\nx\nf\nx\ng\n",
+            "x\nx\na\nb\nc\nd\ne\nf\ng\nx\n",
+            "a\r\nx\r\n\r\nb\r\n",
+            "a\n\n\nx\n\n\nb\nc\nx\nd",
+        ];
+
+        for content in contents {
+            for (before, after) in [(0, 0), (1, 0), (0, 1), (1, 1), (2, 3), (5, 5)] {
+                for max_matches in [1, 2, usize::MAX] {
+                    let groups = ReadBackGrep::new(file(content), is_match)
+                        .unwrap()
+                        .with_context(before, after)
+                        .with_max_matches(max_matches)
+                        .collect::<io::Result<Vec<GrepGroup>>>()
+                        .unwrap();
+
+                    assert_eq!(
+                        groups,
+                        forwards(content, before, after, max_matches),
+                        "content: {:?}, before: {}, after: {}, max matches: {}",
+                        content,
+                        before,
+                        after,
+                        max_matches
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn context_behind_the_start() {
+        let mut file = file("a\nx\nb\nc\nd\n");
+        file.seek(SeekFrom::Start(6)).unwrap();
+
+        let mut grep = ReadBackGrep::new(file, is_match)
+            .unwrap()
+            .with_context(0, 2);
+        let group = grep.next().unwrap().unwrap();
+
+        let lines: Vec<&[u8]> = group
+            .lines
+            .iter()
+            .map(|line| line.data.as_slice())
+            .collect();
+        let expected: [&[u8]; 3] = [b"x", b"b", b"c"];
+        assert_eq!(lines, expected);
+        assert!(grep.next().is_none());
+    }
+}
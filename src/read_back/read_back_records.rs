@@ -0,0 +1,397 @@
+use std::io::{self, ErrorKind, Seek, SeekFrom};
+
+use crate::{BufReadBack, BufReadBacker, ReadBack};
+
+/// The default maximum length of a record, see [`ReadBackRecords::with_max_record_len`].
+pub const DEFAULT_MAX_RECORD_LEN: u64 = 64 * 1024 * 1024;
+
+/// A record which has been read back by [`ReadBackRecords`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The position of the record in the source.
+    pub offset: u64,
+    /// The content of the record, without its trailer.
+    pub data: Vec<u8>,
+}
+
+/// Where a record starts and where the record in front of it ends, see [`ReadBackRecords`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordBounds {
+    /// The position of the start of the record.
+    pub start: u64,
+    /// The position of the end of the trailer of the record in front of it. Everything between it and `start`,
+    /// like padding or an index block, is skipped.
+    pub previous_end: u64,
+}
+
+impl RecordBounds {
+    /// The bounds of a record which directly follows the record in front of it.
+    pub fn contiguous(start: u64) -> Self {
+        Self {
+            start,
+            previous_end: start,
+        }
+    }
+}
+
+/// An iterator over binary records which end with a trailer, starting with the last record.
+///
+/// Each record consists of its data followed by a trailer of a fixed length. The trailer tells where the record
+/// starts, for example by storing its length or the offset of its start (a back-pointer). The trailer is
+/// parsed by a function which gets the trailer and its position in the source and returns the [`RecordBounds`],
+/// or `None` if the trailer is corrupt.
+///
+/// The records don't need to be contiguous: if the previous record ends in front of the start of the record, the
+/// reader seeks to its end, so gaps like padding or index blocks are skipped.
+///
+/// The iterator checks that the start is in front of the trailer and that the record isn't too long, so a corrupt
+/// trailer is reported as an error instead of reading garbage. The iterator stops after the first error.
+///
+/// # Example
+/// ```
+/// use read_collection::{ReadBackRecords, RecordBounds};
+/// use std::io::Write;
+///
+/// fn main() -> std::io::Result<()> {
+///     // every record is followed by its length as a little-endian `u32`
+///     let mut file = tempfile::tempfile()?;
+///     for data in [b"first".as_slice(), b"second"] {
+///         file.write_all(data)?;
+///         file.write_all(&(data.len() as u32).to_le_bytes())?;
+///     }
+///
+///     let records = ReadBackRecords::new(file, 4, |trailer, trailer_offset| {
+///         let len = u32::from_le_bytes(trailer.try_into().ok()?);
+///         trailer_offset.checked_sub(len.into()).map(RecordBounds::contiguous)
+///     })?;
+///
+///     let data = records
+///         .map(|record| record.map(|record| record.data))
+///         .collect::<std::io::Result<Vec<Vec<u8>>>>()?;
+///     assert_eq!(data, [b"second".as_slice(), b"first"]);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ReadBackRecords<R, F> {
+    reader: BufReadBacker<R>,
+    parse: F,
+    trailer_len: usize,
+    max_record_len: u64,
+    /// The position up to which the records haven't been read back yet.
+    pos: u64,
+    failed: bool,
+}
+
+impl<R: ReadBack + Seek, F: FnMut(&[u8], u64) -> Option<RecordBounds>> ReadBackRecords<R, F> {
+    /// Creates a new `ReadBackRecords` which starts reading back at the current position of `reader`.
+    ///
+    /// `parse` gets the `trailer_len` bytes of each trailer and the position of the trailer, which is the end of
+    /// the data of the record.
+    ///
+    /// # Errors
+    /// Returns the error if the position of `reader` can't be determined.
+    ///
+    /// # Panics
+    /// Panics if `trailer_len` is `0`, since empty records wouldn't make any progress.
+    pub fn new(mut reader: R, trailer_len: usize, parse: F) -> io::Result<Self> {
+        assert!(trailer_len > 0, "a record trailer can't be empty");
+        let pos = reader.stream_position()?;
+
+        Ok(Self {
+            reader: BufReadBacker::new(reader),
+            parse,
+            trailer_len,
+            max_record_len: DEFAULT_MAX_RECORD_LEN,
+            pos,
+            failed: false,
+        })
+    }
+}
+
+impl<R, F> ReadBackRecords<R, F> {
+    /// Sets the maximum length of the data of a record. Longer records are treated as corruption.
+    ///
+    /// The default is [`DEFAULT_MAX_RECORD_LEN`].
+    pub fn with_max_record_len(mut self, max_record_len: u64) -> Self {
+        self.max_record_len = max_record_len;
+        self
+    }
+
+    /// Returns the position up to which the records haven't been read back yet.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R: ReadBack + Seek, F: FnMut(&[u8], u64) -> Option<RecordBounds>> ReadBackRecords<R, F> {
+    fn read_back_record(&mut self) -> io::Result<Record> {
+        let trailer_offset = match self.pos.checked_sub(self.trailer_len as u64) {
+            Some(offset) => offset,
+            None => {
+                let message = format!(
+                    "only {} bytes are left for a record trailer of {} bytes",
+                    self.pos, self.trailer_len
+                );
+                return Err(corrupt(message));
+            }
+        };
+
+        let mut trailer = vec![0; self.trailer_len];
+        self.reader.read_back_exact(&mut trailer)?;
+
+        let bounds = match (self.parse)(&trailer, trailer_offset) {
+            Some(bounds) if bounds.start > trailer_offset => {
+                let message = format!(
+                    "record trailer at offset {} points behind itself to offset {}",
+                    trailer_offset, bounds.start
+                );
+                return Err(corrupt(message));
+            }
+            Some(bounds) if trailer_offset - bounds.start > self.max_record_len => {
+                let message = format!(
+                    "record trailer at offset {} describes a record of {} bytes, which is more than the maximum of {}",
+                    trailer_offset,
+                    trailer_offset - bounds.start,
+                    self.max_record_len
+                );
+                return Err(corrupt(message));
+            }
+            Some(bounds) if bounds.previous_end > bounds.start => {
+                let message = format!(
+                    "record trailer at offset {} describes a previous record which ends at offset {}, behind the start {}",
+                    trailer_offset, bounds.previous_end, bounds.start
+                );
+                return Err(corrupt(message));
+            }
+            Some(bounds) => bounds,
+            None => {
+                let message = format!("corrupt record trailer at offset {}", trailer_offset);
+                return Err(corrupt(message));
+            }
+        };
+        let offset = bounds.start;
+
+        let mut data = vec![0; (trailer_offset - offset) as usize];
+        self.reader.read_back_exact(&mut data)?;
+        self.pos = offset;
+        self.skip_to(bounds.previous_end)?;
+
+        Ok(Record { offset, data })
+    }
+
+    /// Skips the gap between the start of the current record and the end of the previous one.
+    fn skip_to(&mut self, previous_end: u64) -> io::Result<()> {
+        let gap = self.pos - previous_end;
+        if gap > 0 {
+            if gap <= self.reader.buffer().len() as u64 {
+                self.reader.read_back_consume(gap as usize);
+            } else {
+                self.reader.discard_buffer();
+                self.reader.get_mut().seek(SeekFrom::Start(previous_end))?;
+            }
+        }
+
+        self.pos = previous_end;
+        Ok(())
+    }
+}
+
+impl<R: ReadBack + Seek, F: FnMut(&[u8], u64) -> Option<RecordBounds>> Iterator
+    for ReadBackRecords<R, F>
+{
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        if self.failed || self.pos == 0 {
+            return None;
+        }
+
+        let record = self.read_back_record();
+        if record.is_err() {
+            self.failed = true;
+        }
+        Some(record)
+    }
+}
+
+fn corrupt(message: String) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs::File,
+        io::{SeekFrom, Write},
+    };
+
+    /// Every record is followed by its start as a big-endian `u64`.
+    fn back_pointers(records: &[&[u8]]) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        let mut offset = 0;
+        for data in records {
+            file.write_all(data).unwrap();
+            file.write_all(&(offset as u64).to_be_bytes()).unwrap();
+            offset += data.len() + 8;
+        }
+        file
+    }
+
+    fn parse(trailer: &[u8], _trailer_offset: u64) -> Option<RecordBounds> {
+        trailer
+            .try_into()
+            .ok()
+            .map(u64::from_be_bytes)
+            .map(RecordBounds::contiguous)
+    }
+
+    #[test]
+    fn newest_to_oldest() {
+        let file = back_pointers(&[b"first", b"", b"third record"]);
+
+        let records = ReadBackRecords::new(file, 8, parse)
+            .unwrap()
+            .collect::<io::Result<Vec<Record>>>()
+            .unwrap();
+
+        assert_eq!(
+            records,
+            [
+                Record {
+                    offset: 21,
+                    data: b"third record".to_vec()
+                },
+                Record {
+                    offset: 13,
+                    data: Vec::new()
+                },
+                Record {
+                    offset: 0,
+                    data: b"first".to_vec()
+                },
+            ]
+        );
+    }
+
+    /// Every record is followed by its start and the end of the previous record as big-endian `u64`s. There
+    /// are `padding` zeros in front of each record.
+    fn padded(records: &[&[u8]], padding: usize) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        let mut previous_end = 0;
+        for data in records {
+            let start = previous_end + padding;
+            file.write_all(&vec![0; padding]).unwrap();
+            file.write_all(data).unwrap();
+            file.write_all(&(start as u64).to_be_bytes()).unwrap();
+            file.write_all(&(previous_end as u64).to_be_bytes())
+                .unwrap();
+            previous_end = start + data.len() + 16;
+        }
+        file
+    }
+
+    fn parse_padded(trailer: &[u8], _trailer_offset: u64) -> Option<RecordBounds> {
+        let (start, previous_end) = trailer.split_at(8);
+        Some(RecordBounds {
+            start: u64::from_be_bytes(start.try_into().ok()?),
+            previous_end: u64::from_be_bytes(previous_end.try_into().ok()?),
+        })
+    }
+
+    #[test]
+    fn skips_padding() {
+        // small and big gaps, which are skipped in the buffer or by seeking
+        for padding in [3, 10_000] {
+            let file = padded(&[b"first", b"", b"third record"], padding);
+
+            let mut records = ReadBackRecords::new(file, 16, parse_padded).unwrap();
+            let data = records
+                .by_ref()
+                .map(|record| record.map(|record| record.data))
+                .collect::<io::Result<Vec<Vec<u8>>>>()
+                .unwrap();
+
+            assert_eq!(
+                data,
+                [b"third record".as_slice(), b"", b"first"],
+                "padding: {}",
+                padding
+            );
+            assert_eq!(records.position(), 0);
+        }
+    }
+
+    #[test]
+    fn previous_end_behind_start() {
+        let file = back_pointers(&[b"first"]);
+
+        let mut records = ReadBackRecords::new(file, 8, |_: &[u8], _| {
+            Some(RecordBounds {
+                start: 0,
+                previous_end: 2,
+            })
+        })
+        .unwrap();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn starts_at_current_position() {
+        let mut file = back_pointers(&[b"first", b"second"]);
+        file.seek(SeekFrom::Start(13)).unwrap();
+
+        let mut records = ReadBackRecords::new(file, 8, parse).unwrap();
+        assert_eq!(records.next().unwrap().unwrap().data, b"first");
+        assert!(records.next().is_none());
+        assert_eq!(records.position(), 0);
+    }
+
+    #[test]
+    fn pointer_behind_trailer() {
+        let mut file = back_pointers(&[b"first", b"second"]);
+        file.write_all(&[0; 4]).unwrap();
+        file.write_all(&100u64.to_be_bytes()).unwrap();
+
+        let mut records = ReadBackRecords::new(file, 8, parse).unwrap();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn too_long_record() {
+        let file = back_pointers(&[b"first", b"second"]);
+
+        let mut records = ReadBackRecords::new(file, 8, parse)
+            .unwrap()
+            .with_max_record_len(5);
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn truncated_trailer() {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&[0; 3]).unwrap();
+
+        let mut records = ReadBackRecords::new(file, 8, parse).unwrap();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejected_trailer() {
+        let file = back_pointers(&[b"first"]);
+
+        let mut records = ReadBackRecords::new(file, 8, |_: &[u8], _| None).unwrap();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    fmt,
+    io::{self, IoSliceMut},
+    time::{Duration, Instant},
+};
+
+use crate::{BorrowedBackBuf, BufReadBack, ReadBack};
+
+/// The default amount of bytes between two calls of the progress callback of a [`ReadBackStats`].
+pub const DEFAULT_PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// The counters of a [`ReadBackStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadBackCounters {
+    /// The calls of `read_back`, `read_back_vectored` and `read_back_buf`.
+    pub read_back_calls: u64,
+    /// The calls of `read_back_fill_buf`.
+    pub fill_buf_calls: u64,
+    /// The calls of `read_back_fill_buf` which returned new data, because the previous buffer had been consumed.
+    pub refills: u64,
+    /// The calls of `read_back_consume`.
+    pub consume_calls: u64,
+    /// The errors which have been returned by the inner reader.
+    pub errors: u64,
+    /// The bytes which have been read back or consumed.
+    pub bytes: u64,
+    /// The time which has been spent in the inner reader.
+    pub inner_time: Duration,
+}
+
+impl ReadBackCounters {
+    /// Returns the bytes per second of the time which has been spent in the inner reader, or `None` if no time
+    /// has been spent in it yet.
+    pub fn throughput(&self) -> Option<f64> {
+        let secs = self.inner_time.as_secs_f64();
+        (secs > 0.0).then(|| self.bytes as f64 / secs)
+    }
+
+    /// Returns the average amount of bytes per refill, or `None` if there hasn't been a refill yet.
+    ///
+    /// Small values mean that the inner reader returns small chunks, so [`BufReadBack::read_back_until`] and
+    /// friends need many calls for long lines.
+    pub fn bytes_per_refill(&self) -> Option<f64> {
+        (self.refills > 0).then(|| self.bytes as f64 / self.refills as f64)
+    }
+}
+
+/// The progress of a [`ReadBackStats`], which is passed to its progress callback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadBackProgress {
+    /// The bytes which have been read back or consumed.
+    pub consumed: u64,
+    /// The amount of bytes in front of the position where the reading back started, if it's known.
+    pub total: Option<u64>,
+    /// The time since the `ReadBackStats` has been created.
+    pub elapsed: Duration,
+    /// Whether the start of the reader has been reached.
+    pub finished: bool,
+}
+
+impl ReadBackProgress {
+    /// Returns the consumed part of the total, between `0.0` and `1.0`, or `None` if the total isn't known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.consumed as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+
+    /// Returns the bytes per second since the `ReadBackStats` has been created.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.consumed as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+type ProgressCallback = Box<dyn FnMut(&ReadBackProgress) + Send>;
+
+/// A [`ReadBack`]er which counts the calls, the bytes and the time spent in the inner reader.
+///
+/// The counters are returned by [`counters`]. They help to find out why reading back is slow, for example if
+/// [`BufReadBack::read_back_until`] refills the buffer for every few bytes.
+///
+/// With [`on_progress`], a callback is told about the consumed bytes every [`DEFAULT_PROGRESS_INTERVAL`] bytes and
+/// once the start has been reached, which can be used to show a progress bar. The total comes from the
+/// [`read_back_size_hint`] of the inner reader if it's exact, or from [`with_total`].
+///
+/// # Example
+/// ```
+/// use read_collection::{BufReadBack, ReadBackStats};
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut reader = ReadBackStats::new(b"first\nsecond\nthird".as_slice());
+///
+///     let mut line = Vec::new();
+///     reader.read_back_until(b'\n', &mut line)?;
+///     assert_eq!(line, b"\nthird");
+///
+///     let counters = reader.counters();
+///     assert_eq!(counters.bytes, 6);
+///     assert_eq!(counters.refills, 1);
+///     assert_eq!(reader.progress().total, Some(18));
+///     Ok(())
+/// }
+/// ```
+///
+/// [`counters`]: ReadBackStats::counters
+/// [`on_progress`]: ReadBackStats::on_progress
+/// [`with_total`]: ReadBackStats::with_total
+/// [`read_back_size_hint`]: ReadBack::read_back_size_hint
+pub struct ReadBackStats<R> {
+    inner: R,
+    counters: ReadBackCounters,
+    /// The unconsumed bytes of the buffer which has been returned by the last `read_back_fill_buf` call.
+    buffered: usize,
+    progress: Progress,
+}
+
+/// The state of the progress reports of a [`ReadBackStats`].
+struct Progress {
+    total: Option<u64>,
+    created: Instant,
+    callback: Option<ProgressCallback>,
+    interval: u64,
+    /// The consumed bytes at the last call of the callback.
+    reported: u64,
+    finished: bool,
+}
+
+impl Progress {
+    fn get(&self, consumed: u64) -> ReadBackProgress {
+        ReadBackProgress {
+            consumed,
+            total: self.total,
+            elapsed: self.created.elapsed(),
+            finished: self.finished,
+        }
+    }
+
+    /// Reports the progress if `interval` bytes have been consumed since the last report.
+    fn update(&mut self, consumed: u64) {
+        if consumed - self.reported >= self.interval.max(1) {
+            self.report(consumed);
+        }
+    }
+
+    /// Reports the progress once the start has been reached.
+    fn finish(&mut self, consumed: u64) {
+        if !self.finished {
+            self.finished = true;
+            self.report(consumed);
+        }
+    }
+
+    fn report(&mut self, consumed: u64) {
+        let progress = self.get(consumed);
+        if let Some(callback) = &mut self.callback {
+            callback(&progress);
+        }
+        self.reported = consumed;
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for ReadBackStats<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadBackStats")
+            .field("inner", &self.inner)
+            .field("counters", &self.counters)
+            .field("total", &self.progress.total)
+            .field("progress_interval", &self.progress.interval)
+            .finish()
+    }
+}
+
+impl<R: ReadBack> ReadBackStats<R> {
+    /// Creates a new `ReadBackStats` around `inner`.
+    ///
+    /// The total for the progress is taken from the [`read_back_size_hint`] of `inner` if its bounds are equal.
+    ///
+    /// [`read_back_size_hint`]: ReadBack::read_back_size_hint
+    pub fn new(inner: R) -> Self {
+        let total = match inner.read_back_size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower as u64),
+            _ => None,
+        };
+
+        Self {
+            inner,
+            counters: ReadBackCounters::default(),
+            buffered: 0,
+            progress: Progress {
+                total,
+                created: Instant::now(),
+                callback: None,
+                interval: DEFAULT_PROGRESS_INTERVAL,
+                reported: 0,
+                finished: false,
+            },
+        }
+    }
+}
+
+impl<R> ReadBackStats<R> {
+    /// Sets the amount of bytes in front of the current position, like the length of a file which is read back
+    /// from its end.
+    pub fn with_total(mut self, total: u64) -> Self {
+        self.progress.total = Some(total);
+        self
+    }
+
+    /// Calls `callback` with the progress every [`DEFAULT_PROGRESS_INTERVAL`] bytes and once the start of the reader
+    /// has been reached.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{ReadBack, ReadBackStats};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let fractions = Arc::new(Mutex::new(Vec::new()));
+    ///     let reported = Arc::clone(&fractions);
+    ///
+    ///     let mut reader = ReadBackStats::new([0; 10].as_slice())
+    ///         .with_progress_interval(4)
+    ///         .on_progress(move |progress| reported.lock().unwrap().push(progress.fraction()));
+    ///
+    ///     let mut buf = [0; 5];
+    ///     reader.read_back_exact(&mut buf)?;
+    ///     reader.read_back_exact(&mut buf)?;
+    ///     assert_eq!(reader.read_back(&mut buf)?, 0);
+    ///
+    ///     assert_eq!(*fractions.lock().unwrap(), [Some(0.5), Some(1.0), Some(1.0)]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&ReadBackProgress) + Send + 'static,
+    {
+        self.progress.callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls the progress callback after `interval` bytes instead of [`DEFAULT_PROGRESS_INTERVAL`] bytes.
+    pub fn with_progress_interval(mut self, interval: u64) -> Self {
+        self.progress.interval = interval;
+        self
+    }
+
+    /// Returns the counters.
+    pub fn counters(&self) -> ReadBackCounters {
+        self.counters
+    }
+
+    /// Sets all counters to zero. The progress isn't changed.
+    pub fn reset_counters(&mut self) {
+        self.counters = ReadBackCounters::default();
+    }
+
+    /// Returns the current progress.
+    pub fn progress(&self) -> ReadBackProgress {
+        self.progress.get(self.counters.bytes)
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading back from it directly isn't counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `ReadBackStats`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Runs `f` with the inner reader and adds the time it took to the counters.
+    fn timed<T>(&mut self, f: impl FnOnce(&mut R) -> io::Result<T>) -> io::Result<T> {
+        let start = Instant::now();
+        let result = f(&mut self.inner);
+        self.counters.inner_time += start.elapsed();
+        if result.is_err() {
+            self.counters.errors += 1;
+        }
+        result
+    }
+
+    /// Counts `amount` read back bytes and reports the progress if needed.
+    fn add_bytes(&mut self, amount: usize) {
+        self.counters.bytes += amount as u64;
+        self.progress.update(self.counters.bytes);
+    }
+}
+
+impl<R: ReadBack> ReadBack for ReadBackStats<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.counters.read_back_calls += 1;
+        let nread = self.timed(|inner| inner.read_back(buf))?;
+        self.buffered = self.buffered.saturating_sub(nread);
+
+        if nread == 0 && !buf.is_empty() {
+            self.progress.finish(self.counters.bytes);
+        } else {
+            self.add_bytes(nread);
+        }
+        Ok(nread)
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.counters.read_back_calls += 1;
+        let nread = self.timed(|inner| inner.read_back_vectored(bufs))?;
+        self.buffered = self.buffered.saturating_sub(nread);
+
+        if nread == 0 && bufs.iter().any(|buf| !buf.is_empty()) {
+            self.progress.finish(self.counters.bytes);
+        } else {
+            self.add_bytes(nread);
+        }
+        Ok(nread)
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        self.inner.is_read_back_vectored()
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> io::Result<()> {
+        self.counters.read_back_calls += 1;
+        let filled = buf.len();
+        let result = self.timed(|inner| inner.read_back_buf(buf));
+
+        // the filled bytes stay valid on errors, so they are counted, too
+        let nread = buf.len() - filled;
+        self.buffered = self.buffered.saturating_sub(nread);
+        if result.is_ok() && nread == 0 && buf.capacity() > filled {
+            self.progress.finish(self.counters.bytes);
+        } else {
+            self.add_bytes(nread);
+        }
+        result
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.read_back_size_hint()
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        self.inner.read_back_advise(amount)
+    }
+}
+
+impl<R: BufReadBack> BufReadBack for ReadBackStats<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.counters.fill_buf_calls += 1;
+
+        let start = Instant::now();
+        let result = self.inner.read_back_fill_buf();
+        self.counters.inner_time += start.elapsed();
+
+        match result {
+            Ok(buf) => {
+                if buf.is_empty() {
+                    self.progress.finish(self.counters.bytes);
+                } else if self.buffered == 0 {
+                    self.counters.refills += 1;
+                }
+                self.buffered = buf.len();
+                Ok(buf)
+            }
+            Err(err) => {
+                self.counters.errors += 1;
+                Err(err)
+            }
+        }
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        self.counters.consume_calls += 1;
+        let amt = std::cmp::min(amt, self.buffered);
+        self.inner.read_back_consume(amt);
+        self.buffered -= amt;
+        self.add_bytes(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing, testing::FailingReadBack, BufReadBacker};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn conformance() {
+        testing::check_buf_read_back(|content| {
+            Box::new(ReadBackStats::new(BufReadBacker::with_capacity(3, content)))
+        });
+    }
+
+    #[test]
+    fn counts_refills_of_read_back_until() {
+        let content = b"a long line\nshort";
+        let mut reader = ReadBackStats::new(BufReadBacker::with_capacity(4, content.as_slice()));
+
+        let mut line = Vec::new();
+        reader.read_back_until(b'\n', &mut line).unwrap();
+        line.clear();
+        reader.read_back_until(b'\n', &mut line).unwrap();
+        assert_eq!(line, b"a long line");
+
+        let counters = reader.counters();
+        assert_eq!(counters.bytes, content.len() as u64);
+        assert_eq!(counters.refills, 5);
+        assert_eq!(counters.errors, 0);
+        assert!(counters.fill_buf_calls >= counters.refills);
+        assert_eq!(counters.bytes_per_refill(), Some(17.0 / 5.0));
+    }
+
+    #[test]
+    fn reports_progress() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reported = Arc::clone(&reports);
+
+        let content = [0; 100];
+        let mut reader = ReadBackStats::new(BufReadBacker::with_capacity(7, content.as_slice()))
+            .with_total(200)
+            .with_progress_interval(30)
+            .on_progress(move |progress| {
+                reported
+                    .lock()
+                    .unwrap()
+                    .push((progress.consumed, progress.finished))
+            });
+
+        let mut buf = [0; 10];
+        while reader.read_back(&mut buf).unwrap() > 0 {}
+        assert!(!reader.read_back_has_data_left().unwrap());
+
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() >= 4, "{:?}", reports);
+        assert!(reports.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(reports.last(), Some(&(100, true)));
+        assert_eq!(reader.progress().fraction(), Some(0.5));
+    }
+
+    #[test]
+    fn counts_errors() {
+        let mut reader = ReadBackStats::new(FailingReadBack::new(b"abc".as_slice(), 1));
+
+        let mut buf = [0; 1];
+        reader.read_back(&mut buf).unwrap();
+        reader.read_back(&mut buf).unwrap_err();
+
+        let counters = reader.counters();
+        assert_eq!(counters.read_back_calls, 2);
+        assert_eq!(counters.errors, 1);
+        assert_eq!(counters.bytes, 1);
+        assert!(!reader.progress().finished);
+    }
+}
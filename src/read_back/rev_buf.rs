@@ -0,0 +1,140 @@
+/// A byte buffer which grows towards its front.
+///
+/// Reading back produces the data from the end to the start, so every new chunk has to be
+/// *pre*pended to the already collected data. Doing that with a plain `Vec` means moving
+/// everything on each chunk, which is quadratic. This buffer keeps its content at the end of
+/// its allocation and doubles the allocation if there's no space left in front, so prepending
+/// is amortized linear in the amount of bytes.
+#[derive(Debug, Default)]
+pub(crate) struct RevBuf {
+    buf: Vec<u8>,
+    start: usize,
+}
+
+impl RevBuf {
+    /// Creates a buffer which already contains `tail`. Everything which gets prepended ends up in front of it.
+    pub fn with_tail(tail: Vec<u8>) -> Self {
+        Self {
+            buf: tail,
+            start: 0,
+        }
+    }
+
+    /// Returns the amount of bytes which are stored.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Returns the stored bytes.
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[self.start..]
+    }
+
+    /// Puts `data` in front of the stored bytes.
+    pub fn prepend(&mut self, data: &[u8]) {
+        self.reserve_front(data.len());
+        self.start -= data.len();
+        self.buf[self.start..self.start + data.len()].copy_from_slice(data);
+    }
+
+    /// Returns the free space in front of the stored bytes, which is at least `min_len` bytes big.
+    ///
+    /// The bytes in it can be "moved" into the stored bytes with [`RevBuf::commit_front`].
+    pub fn front_mut(&mut self, min_len: usize) -> &mut [u8] {
+        self.reserve_front(min_len);
+        &mut self.buf[..self.start]
+    }
+
+    /// Marks the last `amount` bytes of the slice returned by [`RevBuf::front_mut`] as stored.
+    #[inline]
+    pub fn commit_front(&mut self, amount: usize) {
+        assert!(amount <= self.start, "committed more bytes than available");
+        self.start -= amount;
+    }
+
+    /// Removes the stored bytes behind the first `len` ones.
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.buf.truncate(self.start + len);
+    }
+
+    /// Returns the stored bytes as a `Vec`.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.buf.drain(..self.start);
+        self.buf
+    }
+
+    fn reserve_front(&mut self, additional: usize) {
+        if additional <= self.start {
+            return;
+        }
+
+        let len = self.len();
+        let new_capacity = (len * 2).max(len + additional);
+
+        let mut new_buf = vec![0; new_capacity];
+        new_buf[new_capacity - len..].copy_from_slice(self.as_slice());
+
+        self.start = new_capacity - len;
+        self.buf = new_buf;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_BUF_SIZE;
+
+    #[test]
+    fn prepend_keeps_tail() {
+        let mut buffer = RevBuf::with_tail(vec![4, 5]);
+
+        buffer.prepend(&[3]);
+        buffer.prepend(&[1, 2]);
+
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(buffer.into_vec(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn commit_front() {
+        let mut buffer = RevBuf::default();
+
+        buffer.prepend(b"there");
+        let front = buffer.front_mut(6);
+        let front_len = front.len();
+        front[front_len - 6..].copy_from_slice(b"hello ");
+        buffer.commit_front(6);
+
+        assert_eq!(buffer.as_slice(), b"hello there");
+    }
+
+    #[test]
+    fn short_content_stays_small() {
+        let mut buffer = RevBuf::default();
+
+        buffer.prepend(b"line");
+        buffer.prepend(b"\n");
+
+        assert!(buffer.into_vec().capacity() < 16);
+    }
+
+    #[test]
+    fn grows_over_many_prepends() {
+        let mut buffer = RevBuf::default();
+
+        for value in (0..=u8::MAX).rev() {
+            buffer.prepend(&vec![value; DEFAULT_BUF_SIZE / 16]);
+        }
+
+        let content = buffer.into_vec();
+        assert_eq!(content.len(), 256 * (DEFAULT_BUF_SIZE / 16));
+        assert!(content
+            .chunks(DEFAULT_BUF_SIZE / 16)
+            .enumerate()
+            .all(|(index, chunk)| chunk.iter().all(|&b| b as usize == index)));
+    }
+}
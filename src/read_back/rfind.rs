@@ -0,0 +1,274 @@
+use std::io::{self, ErrorKind};
+
+use memchr::memmem::FinderRev;
+
+use crate::BufReadBack;
+
+/// Searches backwards for the last occurrence of the needle of `finder` in front of the current position of `r`.
+///
+/// Returns how many bytes in front of the current position the occurrence starts. Everything from its start on
+/// is consumed. Only the first `needle.len() - 1` bytes of the already searched ones are kept in `carry`, to find
+/// the occurrences which straddle two chunks, so its allocation can be reused between the searches.
+pub(crate) fn rfind<R: BufReadBack + ?Sized>(
+    r: &mut R,
+    finder: &FinderRev,
+    carry: &mut Vec<u8>,
+) -> io::Result<Option<u64>> {
+    let needle_len = finder.needle().len();
+    if needle_len == 0 {
+        return Ok(Some(0));
+    }
+
+    let mut scanned: u64 = 0;
+    carry.clear();
+
+    loop {
+        let chunk = match r.read_back_fill_buf() {
+            Ok(chunk) => chunk,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+
+        // an occurrence which straddles the chunks starts later than one which is completely in the chunk
+        let straddle_len = std::cmp::min(chunk.len(), needle_len - 1);
+        let mut start = None;
+        if !carry.is_empty() {
+            carry.splice(0..0, chunk[chunk.len() - straddle_len..].iter().copied());
+            start = finder
+                .rfind(carry.as_slice())
+                .map(|index| chunk.len() - straddle_len + index);
+        }
+        let start = start.or_else(|| finder.rfind(chunk));
+
+        if let Some(start) = start {
+            let used = chunk.len() - start;
+            r.read_back_consume(used);
+            return Ok(Some(scanned + used as u64));
+        }
+
+        // keep the first bytes, since they might be the end of an occurrence in the next chunk
+        if chunk.len() < needle_len {
+            // if there's a carry, the whole chunk is already in front of it
+            if carry.is_empty() {
+                carry.extend_from_slice(chunk);
+            }
+            carry.truncate(needle_len - 1);
+        } else {
+            carry.clear();
+            carry.extend_from_slice(&chunk[..needle_len - 1]);
+        }
+
+        let used = chunk.len();
+        r.read_back_consume(used);
+        scanned += used as u64;
+    }
+}
+
+/// An iterator over the occurrences of a needle in an instance of [`BufReadBack`], starting with the last one.
+///
+/// This struct is generally created by calling [`rfind_iter`] on a [`BufReadBack`].
+/// Please see the documentation of [`rfind_iter`] for more details.
+///
+/// [`rfind_iter`]: BufReadBack::rfind_iter
+#[derive(Debug)]
+pub struct ReadBackMatches<B> {
+    buf: B,
+    finder: FinderRev<'static>,
+    carry: Vec<u8>,
+    /// The position where the search started, or `0` if the offsets are relative.
+    end: u64,
+    /// The amount of bytes in front of `end` which have been searched.
+    consumed: u64,
+    absolute: bool,
+    done: bool,
+}
+
+impl<B> ReadBackMatches<B> {
+    pub(crate) fn new(buf: B, needle: &[u8]) -> Self {
+        Self {
+            buf,
+            finder: FinderRev::new(needle).into_owned(),
+            carry: Vec::new(),
+            end: 0,
+            consumed: 0,
+            absolute: false,
+            done: false,
+        }
+    }
+
+    /// Reports the occurrences as absolute offsets, given that the search starts at `position`.
+    ///
+    /// For a seekable source, that's the position it had when it has been wrapped.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{BufReadBack, BufReadBacker};
+    /// use std::io::{Seek, SeekFrom, Write};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = tempfile::tempfile()?;
+    ///     file.write_all(b"ok\nERROR: a\nok\nERROR: b\n")?;
+    ///     let end = file.seek(SeekFrom::End(0))?;
+    ///
+    ///     let offsets = BufReadBacker::new(file)
+    ///         .rfind_iter(b"ERROR")
+    ///         .with_position(end)
+    ///         .collect::<std::io::Result<Vec<u64>>>()?;
+    ///
+    ///     assert_eq!(offsets, [15, 3]);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_position(mut self, position: u64) -> Self {
+        self.end = position;
+        self.absolute = true;
+        self
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: BufReadBack> Iterator for ReadBackMatches<B> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<io::Result<u64>> {
+        if self.done {
+            return None;
+        }
+
+        let distance = match rfind(&mut self.buf, &self.finder, &mut self.carry) {
+            Ok(Some(distance)) => distance,
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => {
+                self.done = true;
+                return Some(Err(err));
+            }
+        };
+        self.consumed += distance;
+        let offset = self.consumed;
+
+        // an empty needle matches in front of every byte, so step over one
+        if self.finder.needle().is_empty() {
+            match self.buf.read_back_fill_buf() {
+                Ok([]) => self.done = true,
+                Ok(_) => {
+                    self.buf.read_back_consume(1);
+                    self.consumed += 1;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+
+        if self.absolute {
+            Some(Ok(self.end.saturating_sub(offset)))
+        } else {
+            Some(Ok(offset))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BufReadBack, BufReadBacker, ReadBack};
+
+    /// The expected offsets (in front of the end) of the non-overlapping occurrences, found from the end.
+    fn naive(haystack: &[u8], needle: &[u8]) -> Vec<u64> {
+        let mut offsets = Vec::new();
+        let mut end = haystack.len();
+        while let Some(start) = (0..=end.saturating_sub(needle.len()))
+            .rev()
+            .find(|&start| end >= needle.len() && &haystack[start..start + needle.len()] == needle)
+        {
+            offsets.push((haystack.len() - start) as u64);
+            end = start;
+        }
+        offsets
+    }
+
+    #[test]
+    fn same_as_naive_search() {
+        let haystacks: [&[u8]; 4] = [
+            b"",
+            b"abcabcabc",
+            b"aaaaaaaaaa",
+            b"needle at the start, in the middle: needle and at the end: needle",
+        ];
+        let needles: [&[u8]; 5] = [b"a", b"abc", b"aaa", b"needle", b"missing"];
+
+        for haystack in haystacks {
+            for needle in needles {
+                for capacity in [1, 2, 4, 7, 64] {
+                    let offsets = BufReadBacker::with_capacity(capacity, haystack)
+                        .rfind_iter(needle)
+                        .collect::<std::io::Result<Vec<u64>>>()
+                        .unwrap();
+
+                    assert_eq!(
+                        offsets,
+                        naive(haystack, needle),
+                        "haystack: {:?}, needle: {:?}, capacity: {}",
+                        std::str::from_utf8(haystack).unwrap(),
+                        std::str::from_utf8(needle).unwrap(),
+                        capacity
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rfind_positions_in_front_of_the_match() {
+        let data = b"first needle, second needle, rest";
+        let mut reader = BufReadBacker::with_capacity(3, data.as_slice());
+
+        assert_eq!(reader.rfind(b"needle").ok(), Some(Some(12)));
+
+        let mut rest = Vec::new();
+        reader.read_back_to_end(&mut rest).unwrap();
+        assert_eq!(rest, b"first needle, second ");
+    }
+
+    #[test]
+    fn absolute_offsets_in_file() {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(b"needle, needle, needle, needle").unwrap();
+        // the last occurrence is behind the position
+        file.seek(SeekFrom::Start(25)).unwrap();
+
+        let mut reader = BufReadBacker::with_capacity(4, file);
+        assert_eq!(reader.rfind_offset(b"needle").ok(), Some(Some(16)));
+        assert_eq!(reader.position().ok(), Some(16));
+
+        let offsets = reader
+            .rfind_offsets(b"needle")
+            .unwrap()
+            .collect::<std::io::Result<Vec<u64>>>()
+            .unwrap();
+        assert_eq!(offsets, [8, 0]);
+    }
+
+    #[test]
+    fn empty_needle() {
+        let offsets = b"abc"
+            .as_slice()
+            .rfind_iter(b"")
+            .with_position(3)
+            .collect::<std::io::Result<Vec<u64>>>()
+            .unwrap();
+
+        assert_eq!(offsets, [3, 2, 1, 0]);
+    }
+}
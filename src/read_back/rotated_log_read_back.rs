@@ -0,0 +1,331 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, ErrorKind, IoSliceMut, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crate::{BufReadBack, BufReadBacker, ReadBack, ReadBackConcat};
+
+/// Reads back a log file and its rotated predecessors as one stream.
+///
+/// For a log file like `app.log`, the rotated files `app.log.1`, `app.log.2.gz`, `app.log.3`, ... in the same
+/// directory are discovered as well. The stream starts at the end of `app.log` and continues with the end of
+/// `app.log.1`, and so on, so it looks like all files have been concatenated from the oldest to the newest one.
+///
+/// If a rotated file doesn't end with a new line, a `\n` is inserted behind it, so its last line doesn't
+/// merge with the first line of the next file.
+///
+/// The files are opened once they're reached. Compressed files (`.gz`) require the `gzip` feature and are
+/// decompressed into a [`SpooledReadBack`](crate::SpooledReadBack).
+///
+/// # Example
+/// ```no_run
+/// use read_collection::{BufReadBack, RotatedLogReadBack};
+///
+/// fn main() -> std::io::Result<()> {
+///     let logs = RotatedLogReadBack::open("/var/log/app.log")?;
+///
+///     for line in logs.read_back_lines().take(100) {
+///         println!("{}", line?);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct RotatedLogReadBack {
+    paths: Vec<PathBuf>,
+    files: ReadBackConcat<LogFile>,
+}
+
+impl RotatedLogReadBack {
+    /// Discovers the log file at `path` and its rotated files.
+    ///
+    /// `path` itself doesn't need to exist if there are rotated files.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`ErrorKind::NotFound`] if there are no log files and of kind
+    /// [`ErrorKind::InvalidInput`] if a rotation exists compressed and uncompressed (like `app.log.1` and
+    /// `app.log.1.gz`). Errors while listing the directory are returned as well.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let paths = discover(path.as_ref())?;
+
+        if paths.is_empty() {
+            return Err(io::Error::new(
+                ErrorKind::NotFound,
+                format!("no log files found for {}", path.as_ref().display()),
+            ));
+        }
+
+        let files = paths.iter().enumerate().map(|(index, path)| LogFile {
+            path: path.clone(),
+            // the newest file is the end of the stream, so there's nothing to separate it from
+            terminate: index > 0,
+            reader: None,
+        });
+
+        let files = ReadBackConcat::new(files);
+        Ok(Self { paths, files })
+    }
+
+    /// Returns the paths of the log files, ordered from the newest to the oldest one.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+impl fmt::Debug for RotatedLogReadBack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatedLogReadBack")
+            .field("paths", &self.paths)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadBack for RotatedLogReadBack {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.files.read_back(buf)
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.files.read_back_vectored(bufs)
+    }
+}
+
+impl BufReadBack for RotatedLogReadBack {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.files.read_back_fill_buf()
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        self.files.read_back_consume(amt)
+    }
+}
+
+/// Returns the log file at `path` (if it exists) and its rotations, ordered from the newest to the oldest one.
+fn discover(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "log path has no file name"))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let prefix = format!("{}.", name);
+
+    let mut rotations: Vec<(u64, PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(suffix) = file_name.to_str().and_then(|f| f.strip_prefix(&prefix)) else {
+            continue;
+        };
+
+        let number = suffix.strip_suffix(".gz").unwrap_or(suffix);
+        if let Ok(number) = number.parse::<u64>() {
+            rotations.push((number, entry.path()));
+        }
+    }
+
+    rotations.sort();
+    if let Some(pair) = rotations.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "rotation {} exists twice: {} and {}",
+                pair[0].0,
+                pair[0].1.display(),
+                pair[1].1.display()
+            ),
+        ));
+    }
+
+    let mut paths = Vec::with_capacity(rotations.len() + 1);
+    if path.is_file() {
+        paths.push(path.to_path_buf());
+    }
+    paths.extend(rotations.into_iter().map(|(_, path)| path));
+    Ok(paths)
+}
+
+/// A log file which is opened once it's read back.
+struct LogFile {
+    path: PathBuf,
+    /// Append a new line if the file doesn't end with one.
+    terminate: bool,
+    reader: Option<Box<dyn BufReadBack>>,
+}
+
+impl LogFile {
+    fn reader(&mut self) -> io::Result<&mut Box<dyn BufReadBack>> {
+        if self.reader.is_none() {
+            let mut reader = self.open()?;
+
+            let ends_with_new_line =
+                matches!(reader.read_back_fill_buf()?.last(), None | Some(b'\n'));
+            if self.terminate && !ends_with_new_line {
+                reader = Box::new(b"\n".as_slice().read_back_chain(reader));
+            }
+            self.reader = Some(reader);
+        }
+
+        Ok(self.reader.as_mut().unwrap())
+    }
+
+    fn open(&self) -> io::Result<Box<dyn BufReadBack>> {
+        let mut file = File::open(&self.path)?;
+
+        if self.path.extension().is_some_and(|ext| ext == "gz") {
+            return open_gzip(file, &self.path);
+        }
+
+        file.seek(SeekFrom::End(0))?;
+        Ok(Box::new(BufReadBacker::new(file)))
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn open_gzip(file: File, _path: &Path) -> io::Result<Box<dyn BufReadBack>> {
+    let decoder = flate2::read::MultiGzDecoder::new(io::BufReader::new(file));
+    Ok(Box::new(crate::SpooledReadBack::new(decoder)?))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn open_gzip(_file: File, path: &Path) -> io::Result<Box<dyn BufReadBack>> {
+    Err(io::Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "{} is compressed, which requires the `gzip` feature",
+            path.display()
+        ),
+    ))
+}
+
+impl ReadBack for LogFile {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader()?.read_back(buf)
+    }
+}
+
+impl BufReadBack for LogFile {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader()?.read_back_fill_buf()
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        if let Some(reader) = &mut self.reader {
+            reader.read_back_consume(amt)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn read_back_all(logs: &mut RotatedLogReadBack) -> Vec<u8> {
+        let mut content = Vec::new();
+        logs.read_back_to_end(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn newest_to_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.log"), "5\n6\n").unwrap();
+        fs::write(dir.path().join("app.log.1"), "3\n4").unwrap();
+        fs::write(dir.path().join("app.log.10"), "1\n").unwrap();
+        fs::write(dir.path().join("app.log.2"), "2\n").unwrap();
+        fs::write(dir.path().join("other.log.3"), "unrelated\n").unwrap();
+        fs::write(dir.path().join("app.log.old"), "unrelated\n").unwrap();
+
+        let logs = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap();
+        let names: Vec<_> = logs
+            .paths()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["app.log", "app.log.1", "app.log.2", "app.log.10"]);
+
+        let lines = logs
+            .read_back_lines()
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(lines, ["", "6", "5", "4", "3", "2", "1"]);
+    }
+
+    #[test]
+    fn without_current_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.log.1"), "2").unwrap();
+        fs::write(dir.path().join("app.log.2"), "1").unwrap();
+
+        let mut logs = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap();
+        assert_eq!(read_back_all(&mut logs), b"1\n2");
+    }
+
+    #[test]
+    fn no_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn ambiguous_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.log.1"), "").unwrap();
+        fs::write(dir.path().join("app.log.1.gz"), "").unwrap();
+
+        let err = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn opens_files_lazily() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.log"), "new\n").unwrap();
+        fs::write(dir.path().join("app.log.1"), "old\n").unwrap();
+
+        let mut logs = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap();
+        fs::remove_file(dir.path().join("app.log.1")).unwrap();
+
+        let mut buffer = [0; 4];
+        logs.read_back_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"new\n");
+
+        let err = logs.read_back(&mut buffer).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compressed() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("app.log"), "3\n").unwrap();
+        fs::write(dir.path().join("app.log.1"), "2").unwrap();
+
+        let file = File::create(dir.path().join("app.log.2.gz")).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(b"0\n1").unwrap();
+        encoder.finish().unwrap();
+
+        let mut logs = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap();
+        assert_eq!(read_back_all(&mut logs), b"0\n1\n2\n3\n");
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn compressed_without_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        File::create(dir.path().join("app.log.1.gz"))
+            .and_then(|mut file| file.write_all(b"not read"))
+            .unwrap();
+
+        let mut logs = RotatedLogReadBack::open(dir.path().join("app.log")).unwrap();
+        let err = logs.read_back_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+
+use crate::{BufReadBack, BufReadBacker, ReadBack};
+
+/// Positions `reader` at the start of the first line whose timestamp is at least `target`.
+///
+/// The lines of `reader` have to be ordered by their timestamps, which are extracted by `parse` from each
+/// line (without its `\n` or `\r\n`). Lines without a timestamp (`parse` returns `None`), like the
+/// continuation lines of a stack trace, are skipped. If there's no such line, `reader` is positioned at its end.
+///
+/// The search is a binary search over the byte offsets of `reader`, so only a few lines are read, even for
+/// huge files. Each probe reads back to the start of its line to align to the line boundaries.
+///
+/// # Example
+/// ```
+/// use read_collection::seek_to_time;
+/// use std::io::{BufRead, BufReader, Write};
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut file = tempfile::tempfile()?;
+///     file.write_all(b"10:01 start\n10:02 request\n10:04 request\n10:05 stop\n")?;
+///
+///     let parse = |line: &[u8]| std::str::from_utf8(line.get(..5)?).ok().map(String::from);
+///     let file = seek_to_time(file, &"10:03".to_string(), parse)?;
+///
+///     let lines = BufReader::new(file).lines().collect::<std::io::Result<Vec<String>>>()?;
+///     assert_eq!(lines, ["10:04 request", "10:05 stop"]);
+///     Ok(())
+/// }
+/// ```
+///
+/// # Errors
+/// Returns the errors of `reader`.
+pub fn seek_to_time<R, T, F>(mut reader: R, target: &T, mut parse: F) -> io::Result<R>
+where
+    R: Read + Seek + ReadBack,
+    T: Ord,
+    F: FnMut(&[u8]) -> Option<T>,
+{
+    // The wanted line starts in `lo..=hi` and both are line starts (or the end).
+    let mut lo = 0;
+    let mut hi = reader.seek(SeekFrom::End(0))?;
+
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        let start = line_start(&mut reader, lo, mid)?;
+
+        match next_timestamp(&mut reader, start, hi, &mut parse)? {
+            Some((timestamp, _, end)) if timestamp < *target => lo = end,
+            // the lines in front of the found one don't have a timestamp, so the wanted line is the found one
+            // or in front of `start`
+            _ => hi = start,
+        }
+    }
+
+    // `lo` may point to lines without a timestamp
+    let pos = match next_timestamp(&mut reader, lo, u64::MAX, &mut parse)? {
+        Some((_, start, _)) => start,
+        None => reader.seek(SeekFrom::End(0))?,
+    };
+    reader.seek(SeekFrom::Start(pos))?;
+    Ok(reader)
+}
+
+/// Returns the start of the line which contains `pos`. `lo` has to be a line start in front of it.
+fn line_start<R: Seek + ReadBack>(reader: &mut R, lo: u64, pos: u64) -> io::Result<u64> {
+    reader.seek(SeekFrom::Start(pos))?;
+
+    let mut line = Vec::new();
+    let amount =
+        BufReadBacker::new(reader.read_back_take(pos - lo)).read_back_until(b'\n', &mut line)?;
+
+    match line.first() {
+        Some(b'\n') => Ok(pos - amount as u64 + 1),
+        _ => Ok(lo),
+    }
+}
+
+/// Reads the lines from `start` on until a line has a timestamp and starts in front of `hi`.
+///
+/// Returns its timestamp, its start and the start of the line behind it.
+fn next_timestamp<R, T, F>(
+    reader: &mut R,
+    start: u64,
+    hi: u64,
+    parse: &mut F,
+) -> io::Result<Option<(T, u64, u64)>>
+where
+    R: Read + Seek,
+    F: FnMut(&[u8]) -> Option<T>,
+{
+    reader.seek(SeekFrom::Start(start))?;
+    let mut lines = BufReader::new(reader);
+
+    let mut line = Vec::new();
+    let mut line_start = start;
+    while line_start < hi {
+        line.clear();
+        let amount = lines.read_until(b'\n', &mut line)?;
+        if amount == 0 {
+            break;
+        }
+
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        let content = content.strip_suffix(b"\r").unwrap_or(content);
+        let line_end = line_start + amount as u64;
+        if let Some(timestamp) = parse(content) {
+            return Ok(Some((timestamp, line_start, line_end)));
+        }
+        line_start = line_end;
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs::File, io::Write};
+
+    fn parse(line: &[u8]) -> Option<u32> {
+        std::str::from_utf8(line.get(..2)?).ok()?.parse().ok()
+    }
+
+    fn file(content: &str) -> File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    /// The expected position, found by going through all lines.
+    fn linear(content: &str, target: u32) -> u64 {
+        let mut pos = 0;
+        for line in content.split_inclusive('\n') {
+            if parse(line.as_bytes()).is_some_and(|timestamp| timestamp >= target) {
+                return pos;
+            }
+            pos += line.len() as u64;
+        }
+        content.len() as u64
+    }
+
+    #[test]
+    fn same_as_linear_search() {
+        let contents = [
+            "",
+            "05 single line",
+            "01 a\n02 b\n02 c\n04 d\n07 e\n07 f\n07 g\n08 h\n",
+            "01 a\n03 with a long line which spans over a lot of bytes\n05 c\n06 no trailing new line",
+            "01 a\r\n\tcontinuation\r\n\tmore\r\n03 b\r\n\tcontinuation\r\n05 c\r\n",
+            "no timestamp\n02 a\n02 b\n\n\n04 c\n",
+        ];
+
+        for content in contents {
+            for target in 0..10 {
+                let mut file = seek_to_time(file(content), &target, parse).unwrap();
+                assert_eq!(
+                    file.stream_position().unwrap(),
+                    linear(content, target),
+                    "content: {:?}, target: {}",
+                    content,
+                    target
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn many_lines() {
+        let content: String = (0..1000)
+            .map(|i| format!("{:02} line {}\n", i / 10, i))
+            .collect();
+
+        for target in [0, 1, 42, 99, 100] {
+            let mut file = seek_to_time(file(&content), &target, parse).unwrap();
+            assert_eq!(file.stream_position().unwrap(), linear(&content, target));
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    fs::File,
+    io::{self, IoSliceMut, Read, Seek, SeekFrom, Write},
+};
+
+use crate::{BorrowedBackBuf, BufReadBack, BufReadBacker, ReadBack};
+
+/// The default amount of bytes which are kept in memory before spooling to a temporary file.
+pub const DEFAULT_SPOOL_THRESHOLD: usize = 8 * 1024 * 1024;
+
+/// Makes any [`Read`]er readable backwards by draining it first.
+///
+/// Streams like pipes or [`Stdin`] can't seek, so they can't implement [`ReadBack`]. This struct
+/// reads the whole stream into memory and moves it into a temporary file once it gets bigger than
+/// the threshold. Afterwards, the content can be read back starting from the end of the stream.
+///
+/// The temporary file is removed once the `SpooledReadBack` is dropped.
+///
+/// # Example
+/// ```
+/// use read_collection::{BufReadBack, SpooledReadBack};
+///
+/// fn main() -> std::io::Result<()> {
+///     // could be `std::io::stdin()` as well
+///     let input = b"first\nsecond\nthird".as_slice();
+///
+///     let reader = SpooledReadBack::new(input)?;
+///     let lines = reader.read_back_lines().collect::<std::io::Result<Vec<String>>>()?;
+///
+///     assert_eq!(lines, ["third", "second", "first"]);
+///     Ok(())
+/// }
+/// ```
+///
+/// [`Read`]: std::io::Read
+/// [`Stdin`]: std::io::Stdin
+#[derive(Debug)]
+pub struct SpooledReadBack {
+    spool: Spool,
+}
+
+#[derive(Debug)]
+enum Spool {
+    Memory { data: Vec<u8>, pos: usize },
+    File(BufReadBacker<File>),
+}
+
+impl SpooledReadBack {
+    /// Drains `reader` with the [default threshold](DEFAULT_SPOOL_THRESHOLD).
+    ///
+    /// # Errors
+    /// Returns the errors of `reader` and of creating or writing the temporary file.
+    pub fn new<R: Read>(reader: R) -> io::Result<Self> {
+        Self::with_threshold(DEFAULT_SPOOL_THRESHOLD, reader)
+    }
+
+    /// Drains `reader` and keeps up to `threshold` bytes in memory. Bigger streams are moved into a temporary file.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::{ReadBack, SpooledReadBack};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let input = [1, 2, 3];
+    ///     let mut buffer = Vec::new();
+    ///
+    ///     let mut reader = SpooledReadBack::with_threshold(2, input.as_slice())?;
+    ///     assert!(reader.is_on_disk());
+    ///
+    ///     reader.read_back_to_end(&mut buffer)?;
+    ///     assert_eq!(buffer, input);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the errors of `reader` and of creating or writing the temporary file.
+    pub fn with_threshold<R: Read>(threshold: usize, mut reader: R) -> io::Result<Self> {
+        let mut data = Vec::new();
+        // read one byte more than the threshold to know if the stream is bigger than it
+        (&mut reader)
+            .take((threshold as u64).saturating_add(1))
+            .read_to_end(&mut data)?;
+
+        if data.len() <= threshold {
+            let pos = data.len();
+            return Ok(Self {
+                spool: Spool::Memory { data, pos },
+            });
+        }
+
+        let mut file = tempfile::tempfile()?;
+        file.write_all(&data)?;
+        drop(data);
+        io::copy(&mut reader, &mut file)?;
+        file.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            spool: Spool::File(BufReadBacker::new(file)),
+        })
+    }
+
+    /// Returns `true` if the stream has been moved into a temporary file.
+    pub fn is_on_disk(&self) -> bool {
+        matches!(self.spool, Spool::File(_))
+    }
+}
+
+impl ReadBack for SpooledReadBack {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.spool {
+            Spool::Memory { data, pos } => {
+                let mut rem = &data[..*pos];
+                let amount = rem.read_back(buf)?;
+                *pos = rem.len();
+                Ok(amount)
+            }
+            Spool::File(reader) => reader.read_back(buf),
+        }
+    }
+
+    fn read_back_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        match &mut self.spool {
+            Spool::Memory { data, pos } => {
+                let mut rem = &data[..*pos];
+                let amount = rem.read_back_vectored(bufs)?;
+                *pos = rem.len();
+                Ok(amount)
+            }
+            Spool::File(reader) => reader.read_back_vectored(bufs),
+        }
+    }
+
+    fn read_back_buf(&mut self, buf: &mut BorrowedBackBuf<'_>) -> io::Result<()> {
+        match &mut self.spool {
+            Spool::Memory { data, pos } => {
+                let mut rem = &data[..*pos];
+                rem.read_back_buf(buf)?;
+                *pos = rem.len();
+                Ok(())
+            }
+            Spool::File(reader) => reader.read_back_buf(buf),
+        }
+    }
+
+    fn is_read_back_vectored(&self) -> bool {
+        match &self.spool {
+            Spool::Memory { .. } => true,
+            Spool::File(reader) => reader.is_read_back_vectored(),
+        }
+    }
+
+    fn read_back_size_hint(&self) -> (usize, Option<usize>) {
+        match &self.spool {
+            Spool::Memory { pos, .. } => (*pos, Some(*pos)),
+            Spool::File(reader) => reader.read_back_size_hint(),
+        }
+    }
+
+    fn read_back_advise(&self, amount: usize) {
+        if let Spool::File(reader) = &self.spool {
+            reader.read_back_advise(amount)
+        }
+    }
+}
+
+impl BufReadBack for SpooledReadBack {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        match &mut self.spool {
+            Spool::Memory { data, pos } => Ok(&data[..*pos]),
+            Spool::File(reader) => reader.read_back_fill_buf(),
+        }
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        match &mut self.spool {
+            Spool::Memory { pos, .. } => *pos = pos.saturating_sub(amt),
+            Spool::File(reader) => reader.read_back_consume(amt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn memory() {
+        let reader = SpooledReadBack::with_threshold(3, [1, 2, 3].as_slice()).unwrap();
+        assert!(!reader.is_on_disk());
+    }
+
+    #[test]
+    fn memory_conformance() {
+        testing::check_buf_read_back(|content| {
+            Box::new(SpooledReadBack::with_threshold(usize::MAX, content).unwrap())
+        });
+    }
+
+    #[test]
+    fn file_conformance() {
+        testing::check_buf_read_back_with_config(
+            proptest::test_runner::Config::with_cases(64),
+            |content| Box::new(SpooledReadBack::with_threshold(16, content).unwrap()),
+        );
+    }
+}
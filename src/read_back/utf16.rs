@@ -0,0 +1,370 @@
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use crate::{BufReadBack, ReadBackError, ReadBackOperation};
+
+const NEW_LINE: u16 = 0x000A;
+const CARRIAGE_RETURN: u16 = 0x000D;
+const BOM: u16 = 0xFEFF;
+
+/// The byte order of UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf16Endianness {
+    /// UTF-16LE, which is what Windows usually uses.
+    Little,
+    /// UTF-16BE
+    Big,
+}
+
+impl Utf16Endianness {
+    /// Returns the endianness which is indicated by the byte order mark at the start of `bytes`, if there's one.
+    ///
+    /// # Example
+    /// ```
+    /// use read_collection::Utf16Endianness;
+    ///
+    /// fn main() {
+    ///     assert_eq!(Utf16Endianness::from_bom(&[0xFF, 0xFE, b'a', 0]), Some(Utf16Endianness::Little));
+    ///     assert_eq!(Utf16Endianness::from_bom(&[0xFE, 0xFF, 0, b'a']), Some(Utf16Endianness::Big));
+    ///     assert_eq!(Utf16Endianness::from_bom(b"no bom"), None);
+    /// }
+    /// ```
+    pub fn from_bom(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xFF, 0xFE, ..] => Some(Self::Little),
+            [0xFE, 0xFF, ..] => Some(Self::Big),
+            _ => None,
+        }
+    }
+
+    /// Reads the byte order mark at the start of `reader`, without changing its position.
+    ///
+    /// This is useful for sources which are read back from their end, since the byte order mark is at the start.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use read_collection::{BufReadBack, BufReadBacker, Utf16Endianness};
+    /// use std::fs::File;
+    /// use std::io::{Seek, SeekFrom};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut file = File::open("events.txt")?;
+    ///     file.seek(SeekFrom::End(0))?;
+    ///
+    ///     let endianness = Utf16Endianness::detect(&mut file)?.unwrap_or(Utf16Endianness::Little);
+    ///     for line in BufReadBacker::new(file).read_back_utf16_lines(endianness) {
+    ///         println!("{}", line?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn detect<R: Read + Seek>(reader: &mut R) -> io::Result<Option<Self>> {
+        let pos = reader.stream_position()?;
+
+        reader.seek(SeekFrom::Start(0))?;
+        let mut bom = Vec::with_capacity(2);
+        let result = reader.by_ref().take(2).read_to_end(&mut bom);
+        reader.seek(SeekFrom::Start(pos))?;
+        result?;
+
+        Ok(Self::from_bom(&bom))
+    }
+
+    fn code_unit(self, first: u8, second: u8) -> u16 {
+        match self {
+            Self::Little => u16::from_le_bytes([first, second]),
+            Self::Big => u16::from_be_bytes([first, second]),
+        }
+    }
+}
+
+/// An iterator over the UTF-16 encoded lines of an instance of [`BufReadBack`], starting with the last line.
+///
+/// This struct is generally created by calling [`read_back_utf16_lines`] on a [`BufReadBack`].
+/// Please see the documentation of [`read_back_utf16_lines`] for more details.
+///
+/// [`read_back_utf16_lines`]: BufReadBack::read_back_utf16_lines
+#[derive(Debug)]
+pub struct ReadBackUtf16Lines<B> {
+    buf: B,
+    endianness: Utf16Endianness,
+    /// The later byte of a code unit whose earlier byte hasn't been read back yet.
+    carry: Option<u8>,
+    /// `true` if a new line has been consumed whose line hasn't been returned yet.
+    pending_line: bool,
+    started: bool,
+    /// The amount of consumed bytes, for the offsets of errors.
+    amount_read: u64,
+}
+
+impl<B> ReadBackUtf16Lines<B> {
+    pub(crate) fn new(buf: B, endianness: Utf16Endianness) -> Self {
+        Self {
+            buf,
+            endianness,
+            carry: None,
+            pending_line: false,
+            started: false,
+            amount_read: 0,
+        }
+    }
+
+    fn invalid(&self, offset_from_end: u64, offset_from_start: Option<u64>) -> io::Error {
+        ReadBackError::invalid_text(
+            ReadBackOperation::ReadBackUtf16Lines,
+            "UTF-16",
+            offset_from_end,
+            offset_from_start,
+        )
+        .into()
+    }
+}
+
+impl<B: BufReadBack> ReadBackUtf16Lines<B> {
+    /// Collects the code units (in reverse order) until a new line or the start of the source.
+    /// Returns `true` if a new line has been found (and consumed).
+    fn read_back_until_new_line(&mut self, units: &mut Vec<u16>) -> io::Result<bool> {
+        loop {
+            let chunk = match self.buf.read_back_fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            if chunk.is_empty() {
+                // the source has an odd amount of bytes, so the first one doesn't belong to a code unit
+                return match self.carry {
+                    Some(_) => Err(self.invalid(self.amount_read, Some(0))),
+                    None => Ok(false),
+                };
+            }
+
+            let mut index = chunk.len();
+            let mut found = false;
+
+            if let Some(second) = self.carry.take() {
+                index -= 1;
+                let unit = self.endianness.code_unit(chunk[index], second);
+                found = unit == NEW_LINE;
+                if !found {
+                    units.push(unit);
+                }
+            }
+
+            while !found && index >= 2 {
+                let unit = self
+                    .endianness
+                    .code_unit(chunk[index - 2], chunk[index - 1]);
+                index -= 2;
+                found = unit == NEW_LINE;
+                if !found {
+                    units.push(unit);
+                }
+            }
+
+            if !found && index == 1 {
+                self.carry = Some(chunk[0]);
+                index = 0;
+            }
+
+            let used = chunk.len() - index;
+            self.buf.read_back_consume(used);
+            self.amount_read += used as u64;
+            if found {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl<B: BufReadBack> Iterator for ReadBackUtf16Lines<B> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<io::Result<String>> {
+        let mut units = Vec::new();
+
+        if !self.started {
+            // like `BufRead::lines`: a new line at the end of the source doesn't start another line
+            self.started = true;
+            match self.read_back_until_new_line(&mut units) {
+                Ok(true) if units.is_empty() => self.pending_line = true,
+                Ok(false) if units.is_empty() => return None,
+                Ok(found) => return Some(self.finish_line(units, found)),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        match self.read_back_until_new_line(&mut units) {
+            Ok(false) if units.is_empty() && !self.pending_line => None,
+            Ok(found) => Some(self.finish_line(units, found)),
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl<B> ReadBackUtf16Lines<B> {
+    fn finish_line(&mut self, mut units: Vec<u16>, found_new_line: bool) -> io::Result<String> {
+        self.pending_line = found_new_line;
+
+        units.reverse();
+        if units.last() == Some(&CARRIAGE_RETURN) {
+            units.pop();
+        }
+        // the byte order mark is only at the start of the source, `content_start` is in bytes
+        let mut content_start = 0;
+        if !found_new_line && units.first() == Some(&BOM) {
+            units.remove(0);
+            content_start = 2;
+        }
+
+        String::from_utf16(&units).map_err(|_| {
+            let valid_units: usize = char::decode_utf16(units.iter().copied())
+                .map_while(|c| c.ok())
+                .map(char::len_utf16)
+                .sum();
+            // in bytes from the start of the line
+            let invalid_start = content_start + 2 * valid_units as u64;
+
+            // in bytes from the end, the line starts behind its new line
+            let line_start = self.amount_read - if found_new_line { 2 } else { 0 };
+            self.invalid(
+                line_start - invalid_start,
+                (!found_new_line).then_some(invalid_start),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufReadBacker;
+
+    fn encode(text: &str, endianness: Utf16Endianness) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| match endianness {
+                Utf16Endianness::Little => unit.to_le_bytes(),
+                Utf16Endianness::Big => unit.to_be_bytes(),
+            })
+            .collect()
+    }
+
+    fn lines(data: &[u8], endianness: Utf16Endianness, capacity: usize) -> Vec<String> {
+        BufReadBacker::with_capacity(capacity, data)
+            .read_back_utf16_lines(endianness)
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn same_as_lines() {
+        let texts = [
+            "",
+            "\n",
+            "\n\n",
+            "one line",
+            "first\nsecond\n",
+            "first\r\n\r\nthird",
+            "emoji 👩‍👩‍👧 here\n𝄞 clef\nÄrger",
+        ];
+
+        for text in texts {
+            let expected: Vec<String> = text.lines().rev().map(String::from).collect();
+
+            for endianness in [Utf16Endianness::Little, Utf16Endianness::Big] {
+                let data = encode(text, endianness);
+                // odd capacities split the code units and surrogate pairs between refills
+                for capacity in [1, 3, 5, 64] {
+                    assert_eq!(
+                        lines(&data, endianness, capacity),
+                        expected,
+                        "text: {:?}, capacity: {}",
+                        text,
+                        capacity
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn misaligned_new_line_byte() {
+        // U+0A00 and U+000A share the 0x0A byte, but only the latter is a new line
+        let data = encode("\u{0A00}\u{0A00}\nb", Utf16Endianness::Little);
+
+        assert_eq!(
+            lines(&data, Utf16Endianness::Little, 3),
+            ["b", "\u{0A00}\u{0A00}"]
+        );
+    }
+
+    #[test]
+    fn strips_bom() {
+        let data = encode("\u{FEFF}first\nsecond", Utf16Endianness::Big);
+
+        assert_eq!(Utf16Endianness::from_bom(&data), Some(Utf16Endianness::Big));
+        assert_eq!(lines(&data, Utf16Endianness::Big, 4), ["second", "first"]);
+    }
+
+    #[test]
+    fn detect_keeps_position() {
+        let data = encode("\u{FEFF}text", Utf16Endianness::Little);
+        let mut cursor = std::io::Cursor::new(data);
+        cursor.seek(SeekFrom::End(0)).unwrap();
+
+        assert_eq!(
+            Utf16Endianness::detect(&mut cursor).ok(),
+            Some(Some(Utf16Endianness::Little))
+        );
+        assert_eq!(cursor.position(), 10);
+    }
+
+    #[test]
+    fn odd_amount_of_bytes() {
+        let data = [b'a', 0, b'b'];
+        let mut lines = data
+            .as_slice()
+            .read_back_utf16_lines(Utf16Endianness::Little);
+
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(3));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(0)));
+    }
+
+    #[test]
+    fn unpaired_surrogate() {
+        let data = [0x00, 0xD8, b'\n', 0, b'a', 0];
+        let mut lines = data
+            .as_slice()
+            .read_back_utf16_lines(Utf16Endianness::Little);
+
+        assert_eq!(lines.next().unwrap().ok(), Some("a".to_string()));
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(
+            err.map(|e| e.operation()),
+            Some(ReadBackOperation::ReadBackUtf16Lines)
+        );
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(6));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(Some(0)));
+    }
+
+    #[test]
+    fn unpaired_surrogate_offsets() {
+        let data = encode("\u{FEFF}first\n", Utf16Endianness::Big)
+            .into_iter()
+            .chain([0, b'a', 0xDC, 0x00, 0, b'\n'])
+            .collect::<Vec<u8>>();
+        let mut lines = BufReadBacker::with_capacity(3, data.as_slice())
+            .read_back_utf16_lines(Utf16Endianness::Big);
+
+        let err = lines.next().unwrap().unwrap_err();
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(4));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(None));
+    }
+}
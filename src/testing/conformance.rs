@@ -0,0 +1,554 @@
+use std::{
+    io::{self, ErrorKind, IoSliceMut},
+    mem::MaybeUninit,
+};
+
+use proptest::{
+    collection::vec,
+    prelude::*,
+    test_runner::{Config, TestCaseError, TestRunner},
+};
+
+use crate::{BorrowedBackBuf, BufReadBack, ReadBack};
+
+const MAX_CONTENT_LEN: usize = 2048;
+const MAX_BUF_LEN: usize = 64;
+
+/// Creates a reader for the given content, positioned at its end.
+type NewReadBack<'f> = dyn for<'a> Fn(&'a [u8]) -> Box<dyn ReadBack + 'a> + 'f;
+
+/// Checks that the [`ReadBack`] implementation created by `new_reader` behaves like reading back the given content.
+///
+/// `new_reader` gets the content which the reader should return and has to return a reader which
+/// is positioned at the end of it. The content, the buffer sizes and the split points are
+/// generated with [proptest](https://docs.rs/proptest), so `new_reader` is called a lot.
+///
+/// The following is checked:
+/// - [`read_back`] and [`read_back_buf`] with arbitrary buffer sizes reassemble to the content
+/// - [`read_back_vectored`], [`read_back_to_end`], [`read_back_to_string`] and [`read_back_bytes_buffered`] return
+///   the content
+/// - [`read_back_size_hint`] includes the length of the content
+/// - [`read_back_exact`] returns the tail of the content or fails with [`ErrorKind::UnexpectedEof`]
+/// - [`read_back_take`] and [`read_back_chain`] compose like their `Read` counterparts
+///
+/// # Panics
+/// Panics with the (minimized) failing input if one of the checks fails.
+///
+/// # Example
+/// ```
+/// use read_collection::testing;
+///
+/// testing::check_read_back(|content| Box::new(content));
+/// ```
+///
+/// [`read_back`]: ReadBack::read_back
+/// [`read_back_buf`]: ReadBack::read_back_buf
+/// [`read_back_vectored`]: ReadBack::read_back_vectored
+/// [`read_back_to_end`]: ReadBack::read_back_to_end
+/// [`read_back_to_string`]: ReadBack::read_back_to_string
+/// [`read_back_bytes_buffered`]: ReadBack::read_back_bytes_buffered
+/// [`read_back_size_hint`]: ReadBack::read_back_size_hint
+/// [`read_back_exact`]: ReadBack::read_back_exact
+/// [`read_back_take`]: ReadBack::read_back_take
+/// [`read_back_chain`]: ReadBack::read_back_chain
+pub fn check_read_back<F>(new_reader: F)
+where
+    F: for<'a> Fn(&'a [u8]) -> Box<dyn ReadBack + 'a>,
+{
+    check_read_back_with_config(Config::default(), new_reader)
+}
+
+/// Same as [`check_read_back`] but with a custom [`Config`], for example to change the amount of cases.
+pub fn check_read_back_with_config<F>(config: Config, new_reader: F)
+where
+    F: for<'a> Fn(&'a [u8]) -> Box<dyn ReadBack + 'a>,
+{
+    let mut runner = TestRunner::new(config);
+    let result = runner.run(
+        &(content(), buf_sizes(), any::<prop::sample::Index>()),
+        |(content, sizes, index)| {
+            let at = index.index(content.len() + 1);
+
+            check_read_back_sizes(&new_reader, &content, &sizes)?;
+            check_read_back_buf(&new_reader, &content, &sizes)?;
+            check_read_back_vectored(&new_reader, &content, &sizes)?;
+            check_read_back_to_end(&new_reader, &content, &sizes)?;
+            check_read_back_exact(&new_reader, &content, at)?;
+            check_read_back_bytes(&new_reader, &content)?;
+            check_read_back_take(&new_reader, &content, &sizes, at)?;
+            check_read_back_chain(&new_reader, &content, &sizes, at)?;
+            Ok(())
+        },
+    );
+    unwrap_result(result);
+
+    let mut runner = TestRunner::new(runner.config().clone());
+    let result = runner.run(&(any::<String>(), content()), |(string, tail)| {
+        check_read_back_to_string(&new_reader, &string, &tail)
+    });
+    unwrap_result(result);
+}
+
+/// Checks that the [`BufReadBack`] implementation created by `new_reader` behaves like reading back the given content.
+///
+/// It runs the same checks as [`check_read_back`] and additionally checks that
+/// - [`read_back_fill_buf`] and [`read_back_consume`] reassemble to the content
+/// - [`read_back_until`] and [`read_back_skip_until`] split the content like `rsplit` (keeping the delimiter)
+/// - both work across the boundaries of [`read_back_take`] and [`read_back_chain`]
+///
+/// # Panics
+/// Panics with the (minimized) failing input if one of the checks fails.
+///
+/// # Example
+/// ```
+/// use read_collection::{testing, BufReadBacker};
+///
+/// testing::check_buf_read_back(|content| Box::new(BufReadBacker::with_capacity(3, content)));
+/// ```
+///
+/// [`read_back_fill_buf`]: BufReadBack::read_back_fill_buf
+/// [`read_back_consume`]: BufReadBack::read_back_consume
+/// [`read_back_until`]: BufReadBack::read_back_until
+/// [`read_back_skip_until`]: BufReadBack::read_back_skip_until
+/// [`read_back_take`]: ReadBack::read_back_take
+/// [`read_back_chain`]: ReadBack::read_back_chain
+pub fn check_buf_read_back<F>(new_reader: F)
+where
+    F: for<'a> Fn(&'a [u8]) -> Box<dyn BufReadBack + 'a>,
+{
+    check_buf_read_back_with_config(Config::default(), new_reader)
+}
+
+/// Same as [`check_buf_read_back`] but with a custom [`Config`], for example to change the amount of cases.
+pub fn check_buf_read_back_with_config<F>(config: Config, new_reader: F)
+where
+    F: for<'a> Fn(&'a [u8]) -> Box<dyn BufReadBack + 'a>,
+{
+    check_read_back_with_config(config.clone(), |content| Box::new(new_reader(content)));
+
+    let mut runner = TestRunner::new(config);
+    let result = runner.run(
+        &(
+            delimited_content(),
+            buf_sizes(),
+            any::<prop::sample::Index>(),
+        ),
+        |((content, delim), sizes, index)| {
+            let at = index.index(content.len() + 1);
+
+            let mut reader = new_reader(&content);
+            check_fill_buf_consume(&mut reader, &content, &sizes)?;
+
+            let mut reader = new_reader(&content);
+            check_until(&mut reader, &content, delim)?;
+
+            let mut reader = new_reader(&content[at..]).read_back_chain(new_reader(&content[..at]));
+            check_fill_buf_consume(&mut reader, &content, &sizes)?;
+
+            let mut reader = new_reader(&content[at..]).read_back_chain(new_reader(&content[..at]));
+            check_until(&mut reader, &content, delim)?;
+
+            let limit = (content.len() - at) as u64;
+            let mut take = new_reader(&content).read_back_take(limit);
+            check_fill_buf_consume(&mut take, &content[at..], &sizes)?;
+
+            let mut take = new_reader(&content).read_back_take(limit);
+            check_until(&mut take, &content[at..], delim)?;
+            check_until(&mut take.into_inner(), &content[..at], delim)?;
+            Ok(())
+        },
+    );
+    unwrap_result(result);
+}
+
+fn unwrap_result<T: std::fmt::Debug>(result: Result<(), proptest::test_runner::TestError<T>>) {
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
+
+fn content() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..MAX_CONTENT_LEN)
+}
+
+/// Content with only a few different byte values, so that the delimiter occurs quite often.
+fn delimited_content() -> impl Strategy<Value = (Vec<u8>, u8)> {
+    prop_oneof![
+        (vec(b'a'..=b'd', 0..MAX_CONTENT_LEN), b'a'..=b'd'),
+        (content(), any::<u8>()),
+    ]
+}
+
+fn buf_sizes() -> impl Strategy<Value = Vec<usize>> {
+    vec(1..MAX_BUF_LEN, 1..16)
+}
+
+fn io<T>(result: io::Result<T>) -> Result<T, TestCaseError> {
+    result.map_err(|err| TestCaseError::fail(format!("unexpected I/O error: {}", err)))
+}
+
+/// Reads back everything with the given buffer sizes and returns it in forward order.
+fn read_back_all<R: ReadBack + ?Sized>(
+    reader: &mut R,
+    sizes: &[usize],
+    max_len: usize,
+) -> Result<Vec<u8>, TestCaseError> {
+    let mut read_back = Vec::new();
+
+    for &size in sizes.iter().cycle() {
+        let mut buffer = vec![0; size];
+        let amount = io(reader.read_back(&mut buffer))?;
+        prop_assert!(
+            amount <= size,
+            "read back more bytes than the buffer can hold"
+        );
+
+        if amount == 0 {
+            break;
+        }
+
+        read_back.splice(0..0, buffer[..amount].iter().copied());
+        prop_assert!(
+            read_back.len() <= max_len,
+            "read back more bytes than the source contains"
+        );
+    }
+
+    Ok(read_back)
+}
+
+fn check_read_back_sizes(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let mut reader = new_reader(content);
+
+    prop_assert_eq!(io(reader.read_back(&mut []))?, 0);
+    prop_assert_eq!(read_back_all(&mut reader, sizes, content.len())?, content);
+    prop_assert_eq!(io(reader.read_back(&mut [0; 4]))?, 0, "no data left");
+    Ok(())
+}
+
+fn check_read_back_buf(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let mut reader = new_reader(content);
+    let mut read_back = Vec::new();
+
+    for &size in sizes.iter().cycle() {
+        let mut memory = vec![MaybeUninit::uninit(); size];
+        let mut buf = BorrowedBackBuf::from(memory.as_mut_slice());
+
+        // fill the buffer in two steps, so the reader has to put its bytes in front of filled ones
+        io(reader.read_back_buf(&mut buf))?;
+        io(reader.read_back_buf(&mut buf))?;
+        prop_assert!(
+            buf.len() <= size,
+            "read back more bytes than the buffer can hold"
+        );
+
+        if buf.is_empty() {
+            break;
+        }
+
+        read_back.splice(0..0, buf.filled().iter().copied());
+        prop_assert!(
+            read_back.len() <= content.len(),
+            "read back more bytes than the source contains"
+        );
+    }
+
+    prop_assert_eq!(read_back, content);
+    Ok(())
+}
+
+fn check_read_back_vectored(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let mut reader = new_reader(content);
+    let mut read_back = Vec::new();
+
+    loop {
+        let mut buffers: Vec<Vec<u8>> = sizes.iter().map(|&size| vec![0; size]).collect();
+        let mut slices: Vec<IoSliceMut> = buffers
+            .iter_mut()
+            .map(|buffer| IoSliceMut::new(buffer))
+            .collect();
+
+        let amount = io(reader.read_back_vectored(&mut slices))?;
+        if amount == 0 {
+            break;
+        }
+        prop_assert!(amount <= sizes.iter().sum::<usize>());
+
+        // The buffers are filled starting with the last one, each with the bytes in front of the
+        // ones of the buffer behind it. The bytes of a partially filled buffer are at its start.
+        let mut left = amount;
+        for buffer in buffers.iter().rev() {
+            let filled = std::cmp::min(left, buffer.len());
+            read_back.splice(0..0, buffer[..filled].iter().copied());
+            left -= filled;
+        }
+        prop_assert!(read_back.len() <= content.len());
+    }
+
+    prop_assert_eq!(read_back, content);
+    Ok(())
+}
+
+fn check_read_back_to_end(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let tail = b"tail";
+
+    let mut reader = new_reader(content);
+    let mut buffer = tail.to_vec();
+    prop_assert_eq!(io(reader.read_back_to_end(&mut buffer))?, content.len());
+    prop_assert_eq!(&buffer[..content.len()], content);
+    prop_assert_eq!(&buffer[content.len()..], tail);
+    prop_assert_eq!(io(reader.read_back(&mut [0; 4]))?, 0, "no data left");
+
+    // after some bytes have already been read back
+    let mut reader = new_reader(content);
+    let mut first = vec![0; sizes[0]];
+    let amount = io(reader.read_back(&mut first))?;
+
+    let mut buffer = Vec::new();
+    prop_assert_eq!(
+        io(reader.read_back_to_end(&mut buffer))?,
+        content.len() - amount
+    );
+    prop_assert_eq!(buffer, &content[..content.len() - amount]);
+    Ok(())
+}
+
+fn check_read_back_to_string(
+    new_reader: &NewReadBack,
+    string: &str,
+    invalid: &[u8],
+) -> Result<(), TestCaseError> {
+    let tail = "tail";
+
+    let mut reader = new_reader(string.as_bytes());
+    let mut buffer = tail.to_string();
+    prop_assert_eq!(io(reader.read_back_to_string(&mut buffer))?, string.len());
+    prop_assert_eq!(buffer, format!("{}{}", string, tail));
+    prop_assert_eq!(io(reader.read_back(&mut [0; 4]))?, 0, "no data left");
+
+    if std::str::from_utf8(invalid).is_err() {
+        let mut buffer = tail.to_string();
+        let result = new_reader(invalid).read_back_to_string(&mut buffer);
+
+        prop_assert_eq!(
+            result.map_err(|err| err.kind()),
+            Err(ErrorKind::InvalidData)
+        );
+        prop_assert_eq!(buffer, tail, "the buffer has to stay untouched on error");
+    }
+    Ok(())
+}
+
+fn check_read_back_exact(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    at: usize,
+) -> Result<(), TestCaseError> {
+    let mut reader = new_reader(content);
+    let mut buffer = vec![0; content.len() - at];
+    io(reader.read_back_exact(&mut buffer))?;
+    prop_assert_eq!(&buffer, &content[at..]);
+
+    let mut rest = Vec::new();
+    io(reader.read_back_to_end(&mut rest))?;
+    prop_assert_eq!(rest, &content[..at]);
+
+    let mut reader = new_reader(content);
+    let mut buffer = vec![0; content.len() + 1];
+    prop_assert_eq!(
+        reader
+            .read_back_exact(&mut buffer)
+            .map_err(|err| err.kind()),
+        Err(ErrorKind::UnexpectedEof)
+    );
+    Ok(())
+}
+
+fn check_read_back_bytes(new_reader: &NewReadBack, content: &[u8]) -> Result<(), TestCaseError> {
+    let reader = new_reader(content);
+    let (lower, upper) = reader.read_back_size_hint();
+    prop_assert!(
+        lower <= content.len(),
+        "lower size hint {} is too big",
+        lower
+    );
+    prop_assert!(
+        upper.is_none_or(|upper| upper >= content.len()),
+        "upper size hint {:?} is too small",
+        upper
+    );
+
+    let mut bytes = reader
+        .read_back_bytes_buffered()
+        .collect::<io::Result<Vec<u8>>>();
+    if let Ok(bytes) = bytes.as_mut() {
+        bytes.reverse();
+    }
+
+    prop_assert_eq!(io(bytes)?, content);
+    Ok(())
+}
+
+fn check_read_back_take(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+    at: usize,
+) -> Result<(), TestCaseError> {
+    let limit = (content.len() - at) as u64;
+
+    let mut take = new_reader(content).read_back_take(limit);
+    prop_assert_eq!(
+        read_back_all(&mut take, sizes, content.len())?,
+        &content[at..]
+    );
+    prop_assert_eq!(take.limit(), 0);
+
+    let mut inner = take.into_inner();
+    prop_assert_eq!(
+        read_back_all(&mut inner, sizes, content.len())?,
+        &content[..at]
+    );
+
+    let mut buffer = Vec::new();
+    new_reader(content)
+        .read_back_take(limit)
+        .read_back_to_end(&mut buffer)
+        .map_err(|err| TestCaseError::fail(err.to_string()))?;
+    prop_assert_eq!(buffer, &content[at..]);
+    Ok(())
+}
+
+fn check_read_back_chain(
+    new_reader: &NewReadBack,
+    content: &[u8],
+    sizes: &[usize],
+    at: usize,
+) -> Result<(), TestCaseError> {
+    let (first, second) = content.split_at(at);
+
+    let mut chain = new_reader(second).read_back_chain(new_reader(first));
+    prop_assert_eq!(read_back_all(&mut chain, sizes, content.len())?, content);
+
+    let mut buffer = Vec::new();
+    io(new_reader(second)
+        .read_back_chain(new_reader(first))
+        .read_back_to_end(&mut buffer))?;
+    prop_assert_eq!(buffer, content);
+    Ok(())
+}
+
+fn check_fill_buf_consume<R: BufReadBack + ?Sized>(
+    reader: &mut R,
+    content: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let mut read_back = Vec::new();
+
+    for &size in sizes.iter().cycle() {
+        let buffer = io(reader.read_back_fill_buf())?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        let amount = std::cmp::min(size, buffer.len());
+        read_back.splice(0..0, buffer[buffer.len() - amount..].iter().copied());
+        reader.read_back_consume(amount);
+
+        prop_assert!(read_back.len() <= content.len());
+    }
+
+    prop_assert_eq!(read_back, content);
+    Ok(())
+}
+
+/// Checks `read_back_until` and `read_back_skip_until` against `rsplit` which keeps the delimiter.
+fn check_until<R: BufReadBack + ?Sized>(
+    reader: &mut R,
+    content: &[u8],
+    delim: u8,
+) -> Result<(), TestCaseError> {
+    let mut expected = Vec::new();
+    let mut rest = content;
+    while !rest.is_empty() {
+        let index = rest.iter().rposition(|&b| b == delim).unwrap_or(0);
+        expected.push(rest[index..].to_vec());
+        rest = &rest[..index];
+    }
+
+    for (nr, piece) in expected.iter().enumerate() {
+        if nr % 2 == 0 {
+            let mut buffer = b"tail".to_vec();
+            prop_assert_eq!(io(reader.read_back_until(delim, &mut buffer))?, piece.len());
+            prop_assert_eq!(&buffer[..piece.len()], piece.as_slice());
+            prop_assert_eq!(&buffer[piece.len()..], b"tail");
+        } else {
+            prop_assert_eq!(io(reader.read_back_skip_until(delim))?, piece.len());
+        }
+    }
+
+    let mut buffer = Vec::new();
+    prop_assert_eq!(io(reader.read_back_until(delim, &mut buffer))?, 0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Seek, SeekFrom, Write};
+
+    use super::*;
+    use crate::BufReadBacker;
+
+    fn file_with(content: &[u8]) -> std::fs::File {
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(content).unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file
+    }
+
+    fn config() -> Config {
+        Config::with_cases(64)
+    }
+
+    #[test]
+    fn u8_slice() {
+        check_buf_read_back(|content| Box::new(content));
+    }
+
+    #[test]
+    fn buf_read_backer() {
+        for capacity in [1, 3, 64] {
+            check_buf_read_back_with_config(config(), move |content| {
+                Box::new(BufReadBacker::with_capacity(capacity, content))
+            });
+        }
+    }
+
+    #[test]
+    fn file() {
+        check_read_back_with_config(config(), |content| Box::new(file_with(content)));
+    }
+
+    #[test]
+    fn buf_read_backer_file() {
+        check_buf_read_back_with_config(config(), |content| {
+            Box::new(BufReadBacker::with_capacity(7, file_with(content)))
+        });
+    }
+}
@@ -0,0 +1,348 @@
+use std::{
+    cmp,
+    io::{self, ErrorKind},
+};
+
+use crate::{BufReadBack, ReadBack};
+
+/// A [`ReadBack`]er which reads back at most `max` bytes per call.
+///
+/// Useful to check that callers don't expect a buffer to be filled completely.
+///
+/// # Example
+/// ```
+/// use read_collection::{testing::ShortReadBack, ReadBack};
+///
+/// fn main() {
+///     let data = [1, 2, 3];
+///     let mut buffer = [0; 3];
+///
+///     let mut reader = ShortReadBack::new(data.as_slice(), 2);
+///     assert_eq!(reader.read_back(&mut buffer).ok(), Some(2));
+///     assert_eq!(&buffer[..2], &[2, 3]);
+///
+///     // the default implementations have to cope with it
+///     let mut reader = ShortReadBack::new(data.as_slice(), 1);
+///     assert!(reader.read_back_exact(&mut buffer).is_ok());
+///     assert_eq!(buffer, data);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ShortReadBack<R> {
+    inner: R,
+    max: usize,
+}
+
+impl<R> ShortReadBack<R> {
+    /// Creates a new `ShortReadBack` which reads back at most `max` bytes per call from `inner`.
+    ///
+    /// # Panics
+    /// Panics if `max` is `0` since the reader wouldn't be able to make any progress.
+    pub fn new(inner: R, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "a `ShortReadBack` has to read back at least one byte"
+        );
+        Self { inner, max }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: ReadBack> ReadBack for ShortReadBack<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let max = cmp::min(buf.len(), self.max);
+        self.inner.read_back(&mut buf[..max])
+    }
+}
+
+impl<R: BufReadBack> BufReadBack for ShortReadBack<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self.inner.read_back_fill_buf()?;
+        let start = buf.len().saturating_sub(self.max);
+        Ok(&buf[start..])
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        self.inner.read_back_consume(cmp::min(amt, self.max))
+    }
+}
+
+/// A [`ReadBack`]er which fails with [`ErrorKind::Interrupted`] before every successful call.
+///
+/// Callers are expected to simply retry interrupted calls, as it's the case for [`std::io::Read`].
+///
+/// # Example
+/// ```
+/// use read_collection::{testing::InterruptingReadBack, ReadBack};
+/// use std::io::ErrorKind;
+///
+/// fn main() {
+///     let data = [1, 2, 3];
+///     let mut buffer = [0; 3];
+///
+///     let mut reader = InterruptingReadBack::new(data.as_slice());
+///     let err = reader.read_back(&mut buffer).unwrap_err();
+///     assert_eq!(err.kind(), ErrorKind::Interrupted);
+///     assert_eq!(reader.read_back(&mut buffer).ok(), Some(3));
+///
+///     // the default implementations have to retry
+///     let mut reader = InterruptingReadBack::new(data.as_slice());
+///     let mut collected = Vec::new();
+///     assert_eq!(reader.read_back_to_end(&mut collected).ok(), Some(3));
+///     assert_eq!(collected, data);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InterruptingReadBack<R> {
+    inner: R,
+    interrupt: bool,
+}
+
+impl<R> InterruptingReadBack<R> {
+    /// Creates a new `InterruptingReadBack`. Its first call is going to be interrupted.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            interrupt: true,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn interrupt(&mut self) -> io::Result<()> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                ErrorKind::Interrupted,
+                "injected interruption",
+            ))
+        }
+    }
+}
+
+impl<R: ReadBack> ReadBack for InterruptingReadBack<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt()?;
+        self.inner.read_back(buf)
+    }
+}
+
+impl<R: BufReadBack> BufReadBack for InterruptingReadBack<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.interrupt()?;
+        self.inner.read_back_fill_buf()
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        self.inner.read_back_consume(amt)
+    }
+}
+
+/// A [`ReadBack`]er which fails after `after` bytes have been read back.
+///
+/// The calls before the failure return at most the remaining amount of bytes, so the failure
+/// happens exactly after `after` bytes. Every call afterwards fails as well.
+///
+/// # Example
+/// ```
+/// use read_collection::{testing::FailingReadBack, ReadBack};
+/// use std::io::ErrorKind;
+///
+/// fn main() {
+///     let data = [1, 2, 3];
+///     let mut buffer = [0; 3];
+///
+///     let mut reader = FailingReadBack::new(data.as_slice(), 2);
+///     assert_eq!(reader.read_back(&mut buffer).ok(), Some(2));
+///     assert_eq!(reader.read_back(&mut buffer).unwrap_err().kind(), ErrorKind::Other);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FailingReadBack<R> {
+    inner: R,
+    left: usize,
+    kind: ErrorKind,
+}
+
+impl<R> FailingReadBack<R> {
+    /// Creates a new `FailingReadBack` which fails with [`ErrorKind::Other`] after `after` bytes.
+    pub fn new(inner: R, after: usize) -> Self {
+        Self::with_kind(inner, after, ErrorKind::Other)
+    }
+
+    /// Creates a new `FailingReadBack` which fails with the given `kind` after `after` bytes.
+    pub fn with_kind(inner: R, after: usize, kind: ErrorKind) -> Self {
+        Self {
+            inner,
+            left: after,
+            kind,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fail(&self) -> io::Result<()> {
+        if self.left == 0 {
+            Err(io::Error::new(self.kind, "injected failure"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<R: ReadBack> ReadBack for FailingReadBack<R> {
+    fn read_back(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.fail()?;
+
+        let max = cmp::min(buf.len(), self.left);
+        let amount = self.inner.read_back(&mut buf[..max])?;
+        self.left -= amount;
+        Ok(amount)
+    }
+}
+
+impl<R: BufReadBack> BufReadBack for FailingReadBack<R> {
+    fn read_back_fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.fail()?;
+
+        let buf = self.inner.read_back_fill_buf()?;
+        let start = buf.len().saturating_sub(self.left);
+        Ok(&buf[start..])
+    }
+
+    fn read_back_consume(&mut self, amt: usize) {
+        let amt = cmp::min(amt, self.left);
+        self.left -= amt;
+        self.inner.read_back_consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BufReadBacker, ReadBackError};
+
+    #[test]
+    fn short_conformance() {
+        crate::testing::check_buf_read_back(|content| {
+            Box::new(ShortReadBack::new(
+                BufReadBacker::with_capacity(5, content),
+                2,
+            ))
+        });
+    }
+
+    #[test]
+    fn interrupted_defaults() {
+        let data = b"first\nsecond";
+
+        let mut buffer = [0; 4];
+        let mut reader = InterruptingReadBack::new(data.as_slice());
+        assert!(reader.read_back_exact(&mut buffer).is_ok());
+        assert_eq!(&buffer, b"cond");
+
+        let mut reader = InterruptingReadBack::new(data.as_slice());
+        let mut buffer = Vec::new();
+        assert_eq!(reader.read_back_until(b'\n', &mut buffer).ok(), Some(7));
+        assert_eq!(buffer, b"\nsecond");
+
+        let mut reader = InterruptingReadBack::new(data.as_slice());
+        assert_eq!(reader.read_back_skip_until(b'\n').ok(), Some(7));
+
+        let reader = InterruptingReadBack::new(data.as_slice());
+        let mut bytes = reader
+            .read_back_bytes()
+            .collect::<io::Result<Vec<u8>>>()
+            .unwrap();
+        bytes.reverse();
+        assert_eq!(bytes, data);
+    }
+
+    #[test]
+    fn interrupted_read_back_line() {
+        let data = b"first\r\nsecond";
+
+        let mut reader = InterruptingReadBack::new(data.as_slice());
+        let mut line = String::new();
+        assert_eq!(reader.read_back_line(&mut line).ok(), Some(8));
+        assert_eq!(line, "\r\nsecond");
+
+        let lines = InterruptingReadBack::new(data.as_slice())
+            .read_back_lines()
+            .collect::<io::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(lines, ["second", "first"]);
+    }
+
+    #[test]
+    fn interrupted_invalid_utf8_line() {
+        let data = b"first\n\xFF";
+
+        // the check for the start of the reader is interrupted after the line has been read back
+        let mut reader = InterruptingReadBack::new(data.as_slice());
+        let err = reader.read_back_line(&mut String::new()).unwrap_err();
+
+        let err = err.get_ref().unwrap().downcast_ref::<ReadBackError>();
+        assert_eq!(err.map(|e| e.offset_from_end()), Some(1));
+        assert_eq!(err.map(|e| e.offset_from_start()), Some(None));
+    }
+
+    #[test]
+    fn failing_read_back_to_end() {
+        let data = [1, 2, 3, 4];
+        let mut buffer = Vec::new();
+
+        let mut reader = FailingReadBack::new(data.as_slice(), 2);
+        let err = reader.read_back_to_end(&mut buffer).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn failing_read_back_exact() {
+        let data = [1, 2, 3, 4];
+        let mut buffer = [0; 3];
+
+        let mut reader = FailingReadBack::with_kind(data.as_slice(), 2, ErrorKind::BrokenPipe);
+        let err = reader.read_back_exact(&mut buffer).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn failing_read_back_until() {
+        let data = b"no delimiter in here";
+        let mut buffer = Vec::new();
+
+        let mut reader = FailingReadBack::new(data.as_slice(), 5);
+        assert!(reader.read_back_until(b'\n', &mut buffer).is_err());
+    }
+
+    #[test]
+    fn failing_read_back_bytes() {
+        let data = [1, 2, 3];
+
+        let bytes = FailingReadBack::new(data.as_slice(), 2)
+            .read_back_bytes()
+            .take(3)
+            .collect::<Vec<io::Result<u8>>>();
+
+        assert_eq!(bytes.len(), 3);
+        assert_eq!(bytes[0].as_ref().ok(), Some(&3));
+        assert_eq!(bytes[1].as_ref().ok(), Some(&2));
+        assert_eq!(
+            bytes[2].as_ref().map_err(|err| err.kind()).unwrap_err(),
+            ErrorKind::Other
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Helpers to test your own [`ReadBack`] and [`BufReadBack`] implementations.
+//!
+//! This module is only available with the `testing` feature.
+//!
+//! [`ReadBack`]: crate::ReadBack
+//! [`BufReadBack`]: crate::BufReadBack
+mod conformance;
+mod faults;
+
+pub use conformance::{
+    check_buf_read_back, check_buf_read_back_with_config, check_read_back,
+    check_read_back_with_config,
+};
+pub use faults::{FailingReadBack, InterruptingReadBack, ShortReadBack};
@@ -1 +1,5 @@
+// `file::same_as_read` uses `Read` on a file directly, without handling partial reads or buffering
+#![allow(clippy::unused_io_amount, clippy::unbuffered_bytes)]
+
 pub mod file;
+mod tac;
@@ -0,0 +1,95 @@
+//! Compares the `tac` example with the output of GNU `tac` in `expected/`.
+use std::fs;
+
+#[path = "../../examples/tac.rs"]
+#[allow(dead_code)]
+mod example;
+
+const INPUT_DIR: &str = "./tests/tac/input";
+
+fn check(name: &str, args: &[&str]) {
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| match arg.strip_suffix(".txt") {
+            Some(_) => format!("{}/{}", INPUT_DIR, arg),
+            None => arg.to_string(),
+        })
+        .collect();
+
+    let mut output = Vec::new();
+    example::run(&args, &mut output).unwrap();
+
+    let expected = fs::read(format!("./tests/tac/expected/{}.txt", name)).unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output),
+        String::from_utf8_lossy(&expected),
+        "args: {:?}",
+        args
+    );
+}
+
+#[test]
+fn lines() {
+    check("lines", &["lines.txt"]);
+    check("lines_before", &["-b", "lines.txt"]);
+}
+
+#[test]
+fn no_trailing_newline() {
+    check("no_trailing_newline", &["no_trailing_newline.txt"]);
+}
+
+#[test]
+fn leading_newlines() {
+    check("leading_newlines", &["leading_newlines.txt"]);
+    check(
+        "leading_newlines_before",
+        &["--before", "leading_newlines.txt"],
+    );
+}
+
+#[test]
+fn regex_separator() {
+    check("digits_regex", &["-r", "-s", "[0-9]+", "digits.txt"]);
+    check("digits_regex_before", &["-br", "-s[0-9]+", "digits.txt"]);
+}
+
+#[test]
+fn string_separator() {
+    check("records", &["--separator=<END>", "records.txt"]);
+    check(
+        "records_before",
+        &["-b", "--separator", "<END>", "records.txt"],
+    );
+}
+
+#[test]
+fn multiple_files() {
+    check(
+        "multiple_files",
+        &["lines.txt", "no_trailing_newline.txt", "empty.txt"],
+    );
+}
+
+#[test]
+fn empty() {
+    check("empty", &["empty.txt"]);
+}
+
+/// Bigger than the buffers, so records straddle refills.
+#[test]
+fn log() {
+    check("log", &["log.txt"]);
+    check("log_regex", &["--regex", "-s", "ERROR|WARN", "log.txt"]);
+    check("log_tab_before", &["-b", "-s", "\t", "log.txt"]);
+}
+
+#[test]
+fn invalid_arguments() {
+    let mut output = Vec::new();
+
+    assert!(example::run(&["-x".to_string()], &mut output).is_err());
+    assert!(example::run(&["-s".to_string()], &mut output).is_err());
+    assert!(example::run(&["-s".to_string(), String::new()], &mut output).is_err());
+    assert!(example::run(&["-r".to_string(), "-s(".to_string()], &mut output).is_err());
+}